@@ -0,0 +1,144 @@
+use std::ops::Range;
+
+use ggez::graphics::{DrawParam, Rect};
+use ggez::nalgebra::{Point2, Vector2};
+
+use crate::layout::TILE_SIZE;
+
+/// How fast the camera catches up with its target, higher is snappier
+const FOLLOW_SPEED: f32 = 8.;
+pub const MIN_ZOOM: f32 = 0.25;
+pub const MAX_ZOOM: f32 = 3.;
+
+/// A view over the maze, drawn inside `viewport`, that follows a target and never shows what is
+/// past the maze edges
+#[derive(Debug, Clone)]
+pub struct Camera {
+    /// Top-left corner of the view, in unscaled maze pixels
+    pos: Point2<f32>,
+    zoom: f32,
+    target_zoom: f32,
+    viewport: Rect,
+    /// (width, height) of the maze, in unscaled pixels
+    world: (f32, f32),
+}
+
+impl Camera {
+    pub fn new(viewport: Rect, (w, h): (usize, usize), zoom: f32) -> Camera {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+
+        Camera {
+            pos: Point2::origin(),
+            zoom,
+            target_zoom: zoom,
+            viewport,
+            world: (w as f32 * TILE_SIZE, h as f32 * TILE_SIZE),
+        }
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// The zoom level the camera moves towards, it may go below `MIN_ZOOM` to fit the maze
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.target_zoom = zoom.clamp(self.fit_zoom().min(MIN_ZOOM), MAX_ZOOM);
+    }
+
+    /// The zoom at which the whole maze fits in the viewport
+    pub fn fit_zoom(&self) -> f32 {
+        (self.viewport.w / self.world.0)
+            .min(self.viewport.h / self.world.1)
+            .min(1.)
+    }
+
+    /// Moves smoothly towards `target`, a position in cells, `dt` being the elapsed seconds
+    pub fn follow(&mut self, target: (f32, f32), dt: f32) {
+        let t = 1. - (-FOLLOW_SPEED * dt).exp();
+
+        self.zoom += (self.target_zoom - self.zoom) * t;
+        let wanted = self.clamped(self.centered_on(target));
+        self.pos += (wanted - self.pos) * t;
+        self.pos = self.clamped(self.pos);
+    }
+
+    /// Jumps straight to `target`, a position in cells
+    pub fn center_on(&mut self, target: (f32, f32)) {
+        self.zoom = self.target_zoom;
+        self.pos = self.clamped(self.centered_on(target));
+    }
+
+    /// Size of the view, in unscaled maze pixels
+    fn view_size(&self) -> Vector2<f32> {
+        Vector2::new(self.viewport.w / self.zoom, self.viewport.h / self.zoom)
+    }
+
+    fn centered_on(&self, (x, y): (f32, f32)) -> Point2<f32> {
+        Point2::new((x + 0.5) * TILE_SIZE, (y + 0.5) * TILE_SIZE) - self.view_size() / 2.
+    }
+
+    /// Keeps the view inside the maze, or centers the maze when it is smaller than the view
+    fn clamped(&self, pos: Point2<f32>) -> Point2<f32> {
+        let view = self.view_size();
+        let clamp = |pos: f32, world: f32, view: f32| {
+            if world <= view {
+                (world - view) / 2.
+            } else {
+                pos.clamp(0., world - view)
+            }
+        };
+
+        Point2::new(
+            clamp(pos.x, self.world.0, view.x),
+            clamp(pos.y, self.world.1, view.y),
+        )
+    }
+
+    /// Screen position of a point given in unscaled maze pixels
+    pub fn world_to_screen(&self, p: Point2<f32>) -> Point2<f32> {
+        Point2::new(
+            self.viewport.x + (p.x - self.pos.x) * self.zoom,
+            self.viewport.y + (p.y - self.pos.y) * self.zoom,
+        )
+    }
+
+    /// Screen position of the top-left corner of a cell, fractional cells are allowed
+    pub fn cell_to_screen(&self, (x, y): (f32, f32)) -> Point2<f32> {
+        self.world_to_screen(Point2::new(x * TILE_SIZE, y * TILE_SIZE))
+    }
+
+    /// Position in cells of a point on screen, the inverse of `cell_to_screen`
+    pub fn screen_to_cell(&self, p: Point2<f32>) -> (f32, f32) {
+        (
+            ((p.x - self.viewport.x) / self.zoom + self.pos.x) / TILE_SIZE,
+            ((p.y - self.viewport.y) / self.zoom + self.pos.y) / TILE_SIZE,
+        )
+    }
+
+    /// Parameters to draw the maze and what is on it through this camera
+    pub fn draw_param(&self) -> DrawParam {
+        DrawParam::new()
+            .dest(self.world_to_screen(Point2::origin()))
+            .scale([self.zoom, self.zoom])
+    }
+
+    /// Columns and rows of cells at least partially in view
+    pub fn visible_cells(&self) -> (Range<usize>, Range<usize>) {
+        let view = self.view_size();
+        let range = |pos: f32, view: f32, world: f32| {
+            let start = (pos / TILE_SIZE).floor().max(0.) as usize;
+            let end = ((pos + view) / TILE_SIZE).ceil().min(world / TILE_SIZE) as usize;
+            // walls are drawn slightly bigger than a cell, keep one more around
+            start.saturating_sub(1)..end + 1
+        };
+
+        (
+            range(self.pos.x, view.x, self.world.0),
+            range(self.pos.y, view.y, self.world.1),
+        )
+    }
+}
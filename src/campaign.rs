@@ -0,0 +1,175 @@
+//! A sequence of levels of growing difficulty, described in a JSON file so that they can be tuned
+//! without touching the code, and the player progress through them.
+
+use std::io::Write;
+use std::path::Path;
+
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, Rules, TICKS_PER_SECOND};
+use crate::generator::Algorithm;
+use crate::maze::{Maze, MazeError, Population, MALUSES, REWARDS};
+use crate::storage;
+
+/// Where the progress is kept, in the user directory
+const PROGRESS_PATH: &str = "/progress.json";
+/// Bumped whenever the layout of the progress file changes. It was first saved without a
+/// version, which reads as 0, and did not change since.
+const VERSION: u32 = 0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Level {
+    pub name: String,
+    /// (width, height), in tiles, both odd and at least 7
+    pub size: (usize, usize),
+    #[serde(default = "default_rewards")]
+    pub rewards: usize,
+    #[serde(default = "default_maluses")]
+    pub maluses: usize,
+    /// How long the maze is shown, in seconds
+    pub preview: f32,
+    /// How long the player has once the fog fell, in seconds
+    #[serde(default)]
+    pub time_limit: Option<f32>,
+    /// Health the player starts with, each malus and enemy bite takes one
+    #[serde(default = "default_health")]
+    pub health: u8,
+    /// Number of enemy crabs roaming the maze
+    #[serde(default)]
+    pub enemies: u8,
+    /// How far the player sees under the fog, in cells
+    #[serde(default = "default_view_radius")]
+    pub view_radius: u8,
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    /// Makes the level the same maze every time
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_rewards() -> usize {
+    REWARDS
+}
+
+fn default_maluses() -> usize {
+    MALUSES
+}
+
+fn default_health() -> u8 {
+    Rules::default().health
+}
+
+fn default_view_radius() -> u8 {
+    Rules::default().view_radius
+}
+
+impl Level {
+    /// A game on this level, `seed` being used unless the level has its own
+    pub fn new_game(&self, seed: u64) -> GameResult<Game> {
+        let maze = self.maze(seed)?;
+
+        let rules = Rules {
+            preview_ticks: secs_to_ticks(self.preview).max(1),
+            time_limit: self.time_limit.map(secs_to_ticks),
+            health: self.health.max(1),
+            enemies: self.enemies,
+            view_radius: self.view_radius.max(1),
+            ..Rules::for_maze(&maze)
+        };
+        Ok(Game::new(maze, rules))
+    }
+
+    /// The maze of this level, `seed` being used unless the level has its own
+    fn maze(&self, seed: u64) -> Result<Maze, MazeError> {
+        let population = Population {
+            rewards: self.rewards,
+            maluses: self.maluses,
+        };
+        let generator = self.algorithm.generator();
+        Maze::from_seed_with(
+            self.size,
+            self.seed.unwrap_or(seed),
+            &*generator,
+            population,
+        )
+    }
+}
+
+fn secs_to_ticks(secs: f32) -> u64 {
+    (secs.max(0.) * TICKS_PER_SECOND as f32).round() as u64
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    pub levels: Vec<Level>,
+}
+
+impl Campaign {
+    pub fn load(path: &Path) -> GameResult<Campaign> {
+        let invalid = |reason: String| {
+            GameError::ConfigError(format!("invalid campaign {}: {}", path.display(), reason))
+        };
+
+        let campaign: Campaign = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| invalid(e.to_string()))?;
+
+        if campaign.levels.is_empty() {
+            return Err(invalid("there are no levels".to_owned()));
+        }
+        for level in &campaign.levels {
+            let (w, h) = level.size;
+            if w % 2 == 0 || h % 2 == 0 || w < 7 || h < 7 {
+                return Err(invalid(format!("{} has an invalid size", level.name)));
+            }
+            // rather than failing once played, which ends the game
+            if let Err(e) = level.maze(0) {
+                return Err(invalid(format!(
+                    "{} cannot be generated: {}",
+                    level.name, e
+                )));
+            }
+        }
+
+        Ok(campaign)
+    }
+
+    /// (width, height) of the biggest maze, in tiles
+    pub fn max_size(&self) -> (usize, usize) {
+        self.levels.iter().fold((0, 0), |(w, h), level| {
+            (w.max(level.size.0), h.max(level.size.1))
+        })
+    }
+}
+
+/// How far the player went in the campaign
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Progress {
+    #[serde(default)]
+    version: u32,
+    /// Number of levels cleared, the next one is unlocked
+    pub cleared: usize,
+}
+
+impl Progress {
+    /// Reads the saved progress, starting over when there is none or it cannot be used
+    pub fn load(ctx: &mut Context) -> Progress {
+        storage::load(ctx, PROGRESS_PATH, VERSION, "the campaign progress").unwrap_or_default()
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let text = serde_json::to_string(self).expect("progress always serializes");
+        filesystem::create(ctx, PROGRESS_PATH)?.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    /// Records that `level` was cleared, which unlocks the following one
+    pub fn clear(&mut self, level: usize) {
+        self.cleared = self.cleared.max(level + 1);
+    }
+
+    /// Index of the first level not cleared yet, or of the last one when all were
+    pub fn current(&self, campaign: &Campaign) -> usize {
+        self.cleared.min(campaign.levels.len() - 1)
+    }
+}
@@ -0,0 +1,193 @@
+//! Keys bound to the actions of the game, kept in the user directory. They are changed from the
+//! controls screen, or by hand in the file, keys being named as in `KeyCode`. Actions missing
+//! from the file keep their default keys.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use ggez::input::keyboard::{self, KeyCode};
+use ggez::{filesystem, Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::maze::Direction;
+use crate::storage;
+
+/// Where the controls are kept, in the user directory
+const CONTROLS_PATH: &str = "/controls.json";
+/// Bumped whenever the file layout changes
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveNorth,
+    MoveEast,
+    MoveSouth,
+    MoveWest,
+    Pause,
+    Restart,
+    Hint,
+    ToggleGhost,
+    ToggleMinimap,
+    ZoomIn,
+    ZoomOut,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::MoveNorth,
+        Action::MoveEast,
+        Action::MoveSouth,
+        Action::MoveWest,
+        Action::Pause,
+        Action::Restart,
+        Action::Hint,
+        Action::ToggleGhost,
+        Action::ToggleMinimap,
+        Action::ZoomIn,
+        Action::ZoomOut,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveNorth => "Move up",
+            Action::MoveEast => "Move right",
+            Action::MoveSouth => "Move down",
+            Action::MoveWest => "Move left",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::Hint => "Hint",
+            Action::ToggleGhost => "Ghost",
+            Action::ToggleMinimap => "Full map",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+        }
+    }
+
+    /// The action walking towards `dir`
+    pub fn moving(dir: Direction) -> Action {
+        match dir {
+            Direction::North => Action::MoveNorth,
+            Direction::East => Action::MoveEast,
+            Direction::South => Action::MoveSouth,
+            Direction::West => Action::MoveWest,
+        }
+    }
+
+    /// Where the action walks, if it is a move
+    pub fn direction(self) -> Option<Direction> {
+        match self {
+            Action::MoveNorth => Some(Direction::North),
+            Action::MoveEast => Some(Direction::East),
+            Action::MoveSouth => Some(Direction::South),
+            Action::MoveWest => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    /// Arrows, WASD for QWERTY keyboards and ZQSD for AZERTY ones all move
+    fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            Action::MoveNorth => vec![KeyCode::Up, KeyCode::W, KeyCode::Z],
+            Action::MoveEast => vec![KeyCode::Right, KeyCode::D],
+            Action::MoveSouth => vec![KeyCode::Down, KeyCode::S],
+            Action::MoveWest => vec![KeyCode::Left, KeyCode::A, KeyCode::Q],
+            Action::Pause => vec![KeyCode::Escape, KeyCode::P],
+            Action::Restart => vec![KeyCode::R],
+            Action::Hint => vec![KeyCode::H],
+            Action::ToggleGhost => vec![KeyCode::G],
+            Action::ToggleMinimap => vec![KeyCode::M],
+            Action::ZoomIn => vec![KeyCode::Add, KeyCode::Equals],
+            Action::ZoomOut => vec![KeyCode::Subtract, KeyCode::Minus],
+        }
+    }
+}
+
+/// The keys of every action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Controls {
+    version: u32,
+    bindings: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Controls {
+            version: VERSION,
+            bindings: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl Controls {
+    /// Reads the saved controls, the default ones are used when there are none or they cannot be
+    /// read
+    pub fn load(ctx: &mut Context) -> Controls {
+        let mut controls: Controls =
+            match storage::load(ctx, CONTROLS_PATH, VERSION, "the controls") {
+                Some(controls) => controls,
+                None => return Controls::default(),
+            };
+
+        for action in &Action::ALL {
+            controls
+                .bindings
+                .entry(*action)
+                .or_insert_with(|| action.default_keys());
+        }
+        controls
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let text = serde_json::to_string_pretty(self).expect("controls always serialize");
+        filesystem::create(ctx, CONTROLS_PATH)?.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The action `key` is bound to, if any
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    /// Whether a key bound to `action` is held down
+    pub fn is_pressed(&self, ctx: &Context, action: Action) -> bool {
+        self.keys(action)
+            .iter()
+            .any(|key| keyboard::is_key_pressed(ctx, *key))
+    }
+
+    /// Makes `key` the only key of `action`, unless another action uses it, which is returned
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), Action> {
+        match self.action(key) {
+            Some(other) if other != action => Err(other),
+            _ => {
+                self.bindings.insert(action, vec![key]);
+                Ok(())
+            }
+        }
+    }
+
+    /// The keys of `action`, as written on the controls screen
+    pub fn describe(&self, action: Action) -> String {
+        let keys: Vec<_> = self
+            .keys(action)
+            .iter()
+            .map(|key| format!("{:?}", key))
+            .collect();
+
+        if keys.is_empty() {
+            "-".to_owned()
+        } else {
+            keys.join(", ")
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Crabs roaming the maze. They patrol by keeping a wall on their right, and chase the player
+//! along the shortest path once they see it down a corridor, until it gets too far away.
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+use crate::maze::{CellIndex, Direction, Maze, DIRECTIONS};
+use crate::pathfinding;
+
+/// Number of ticks needed to walk from one cell to the next one while patrolling
+pub const PATROL_STEP_TICKS: u32 = 16;
+/// Number of ticks needed to walk from one cell to the next one while chasing, a bit slower
+/// than the player
+pub const CHASE_STEP_TICKS: u32 = 12;
+/// How far enemies see down a straight corridor, in cells
+pub const SIGHT_RANGE: usize = 6;
+/// Enemies give up the chase when the player is further than this, in steps
+pub const CHASE_RANGE: usize = 12;
+/// Enemies start at least this many steps away from the player, when the maze is big enough
+const SPAWN_DISTANCE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyState {
+    Patrol,
+    Chase,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Translation {
+    to: CellIndex,
+    progress: u32,
+    /// Ticks the whole step takes
+    duration: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Enemy {
+    cell: CellIndex,
+    heading: Direction,
+    state: EnemyState,
+    current_translation: Option<Translation>,
+    flipped: bool,
+    /// Which animation set it is drawn with
+    kind: usize,
+}
+
+impl Enemy {
+    pub fn new(cell: CellIndex, heading: Direction, kind: usize) -> Enemy {
+        Enemy {
+            cell,
+            heading,
+            state: EnemyState::Patrol,
+            current_translation: None,
+            flipped: false,
+            kind,
+        }
+    }
+
+    /// The cell the enemy is standing on, a moving enemy is still on its departure cell
+    pub fn cell(&self) -> CellIndex {
+        self.cell
+    }
+
+    /// The current cell and the one being walked to
+    pub fn cells(&self) -> impl Iterator<Item = CellIndex> {
+        std::iter::once(self.cell).chain(self.current_translation.map(|t| t.to))
+    }
+
+    /// Interpolated position, in cells
+    pub fn pos(&self) -> (f32, f32) {
+        let (x, y) = (self.cell.x as f32, self.cell.y as f32);
+
+        match self.current_translation {
+            Some(translation) => {
+                let t = translation.progress as f32 / translation.duration as f32;
+                (
+                    x + (translation.to.x as f32 - x) * t,
+                    y + (translation.to.y as f32 - y) * t,
+                )
+            }
+            None => (x, y),
+        }
+    }
+
+    pub fn state(&self) -> EnemyState {
+        self.state
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.current_translation.is_some()
+    }
+
+    pub fn is_flipped(&self) -> bool {
+        self.flipped
+    }
+
+    pub fn kind(&self) -> usize {
+        self.kind
+    }
+
+    /// Advances the current movement by one tick, and picks the next cell once on a cell,
+    /// `player` being the cell the player stands on
+    pub fn update(&mut self, maze: &Maze, player: CellIndex) {
+        if let Some(translation) = &mut self.current_translation {
+            translation.progress += 1;
+
+            if translation.progress >= translation.duration {
+                self.cell = translation.to;
+                self.current_translation = None;
+            }
+        }
+
+        if self.current_translation.is_none() {
+            if let Some(dir) = self.next_direction(maze, player) {
+                self.translate(maze, dir);
+            }
+        }
+    }
+
+    fn next_direction(&mut self, maze: &Maze, player: CellIndex) -> Option<Direction> {
+        if self.sees(maze, player) {
+            self.state = EnemyState::Chase;
+        }
+
+        if self.state == EnemyState::Chase {
+            match pathfinding::find_path(maze, self.cell, player, |_| true) {
+                Some(path) if path.len() <= CHASE_RANGE => {
+                    // standing on the player, waiting for it to move
+                    let next = *path.first()?;
+                    return pathfinding::direction_to(maze, self.cell, next);
+                }
+                _ => self.state = EnemyState::Patrol,
+            }
+        }
+
+        let heading = self.heading;
+        [heading.right(), heading, heading.left(), heading.opposite()]
+            .iter()
+            .copied()
+            .find(|dir| self.is_open(maze, *dir))
+    }
+
+    /// Whether `target` is down a straight corridor, close enough
+    fn sees(&self, maze: &Maze, target: CellIndex) -> bool {
+        let (dx, dy) = (
+            self.cell.x.abs_diff(target.x),
+            self.cell.y.abs_diff(target.y),
+        );
+        if (dx != 0 && dy != 0) || dx.max(dy) > SIGHT_RANGE {
+            return false;
+        }
+
+        let (x_range, y_range) = (
+            self.cell.x.min(target.x)..=self.cell.x.max(target.x),
+            self.cell.y.min(target.y)..=self.cell.y.max(target.y),
+        );
+        x_range
+            .flat_map(|x| y_range.clone().map(move |y| CellIndex::from([x, y])))
+            .all(|pos| !maze.get(pos).is_wall())
+    }
+
+    fn is_open(&self, maze: &Maze, dir: Direction) -> bool {
+        maze.neighbour(self.cell, dir)
+            .is_some_and(|next| !maze.get(next).is_wall())
+    }
+
+    fn translate(&mut self, maze: &Maze, dir: Direction) {
+        let to = match maze.neighbour(self.cell, dir) {
+            Some(to) => to,
+            None => return,
+        };
+
+        match dir {
+            Direction::West => self.flipped = true,
+            Direction::East => self.flipped = false,
+            _ => {}
+        }
+        self.heading = dir;
+        self.current_translation = Some(Translation {
+            to,
+            progress: 0,
+            duration: match self.state {
+                EnemyState::Patrol => PATROL_STEP_TICKS,
+                EnemyState::Chase => CHASE_STEP_TICKS,
+            },
+        });
+    }
+}
+
+/// Places `count` enemies far enough from the start, always at the same cells for a given maze
+pub fn spawn(maze: &Maze, count: usize) -> Vec<Enemy> {
+    let distances = maze.distances(maze.start(), |_| true);
+    let farthest = distances.iter().flatten().copied().max().unwrap_or(0);
+    let min_distance = SPAWN_DISTANCE.min(farthest);
+    let (w, _) = maze.dim();
+
+    let mut cells: Vec<_> = distances
+        .iter()
+        .enumerate()
+        .filter(|(_, d)| d.is_some_and(|d| d >= min_distance && d > 0))
+        .map(|(i, _)| CellIndex::from([i % w, i / w]))
+        .filter(|pos| *pos != maze.exit() && maze.get_reward(*pos).is_none())
+        .collect();
+    let mut rng = Pcg32::seed_from_u64(maze.seed().unwrap_or(0));
+    cells.shuffle(&mut rng);
+
+    cells
+        .into_iter()
+        .take(count)
+        .enumerate()
+        .map(|(i, cell)| {
+            let heading = DIRECTIONS
+                .iter()
+                .copied()
+                .find(|dir| {
+                    maze.neighbour(cell, *dir)
+                        .is_some_and(|n| !maze.get(n).is_wall())
+                })
+                .unwrap_or(Direction::North);
+            Enemy::new(cell, heading, i)
+        })
+        .collect()
+}
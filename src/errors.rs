@@ -0,0 +1,25 @@
+//! The errors of the game logic, which knows nothing of ggez, turned into ggez ones
+
+use ggez::GameError;
+
+use crate::formats::FormatError;
+use crate::maze::MazeError;
+use crate::tmx::TmxError;
+
+impl From<MazeError> for GameError {
+    fn from(e: MazeError) -> Self {
+        GameError::ConfigError(e.to_string())
+    }
+}
+
+impl From<TmxError> for GameError {
+    fn from(e: TmxError) -> Self {
+        GameError::ConfigError(e.to_string())
+    }
+}
+
+impl From<FormatError> for GameError {
+    fn from(e: FormatError) -> Self {
+        GameError::ConfigError(e.to_string())
+    }
+}
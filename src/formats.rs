@@ -0,0 +1,348 @@
+//! Text formats to keep and share mazes: Tiled maps, ASCII grids and JSON documents. Importing an
+//! exported maze gives back an identical `Maze`, except for ASCII grids which have no room for the
+//! seed nor for found rewards. Mazes with more than one thing on a cell are not valid, so that
+//! every marker of a grid has a cell of its own.
+
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::maze::{CellIndex, Maze, MazeError};
+use crate::rewards::Reward;
+use crate::tile::Tile;
+use crate::tmx::{self, TmxError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Tmx,
+    /// One line per row: `#` wall, `.` ground, `A` apple, `X` malus, `S` start, `E` exit
+    Ascii,
+    Json,
+}
+
+impl Format {
+    /// Guesses the format from the extension of `path`: `tmx`, `txt` or `json`
+    pub fn from_path(path: &Path) -> Result<Format, FormatError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("tmx") => Ok(Format::Tmx),
+            Some("txt") => Ok(Format::Ascii),
+            Some("json") => Ok(Format::Json),
+            _ => Err(FormatError::UnknownExtension(path.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Tmx(TmxError),
+    Json(serde_json::Error),
+    Maze(MazeError),
+    /// The text parsed but does not describe a playable maze
+    Invalid(String),
+    UnknownExtension(PathBuf),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Tmx(e) => write!(f, "{}", e),
+            FormatError::Json(e) => write!(f, "invalid json maze: {}", e),
+            FormatError::Maze(e) => write!(f, "invalid maze: {}", e),
+            FormatError::Invalid(reason) => write!(f, "invalid maze: {}", reason),
+            FormatError::UnknownExtension(path) => write!(
+                f,
+                "unknown maze format for {}, expected a .tmx, .txt or .json file",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for FormatError {}
+
+impl From<TmxError> for FormatError {
+    fn from(e: TmxError) -> Self {
+        FormatError::Tmx(e)
+    }
+}
+
+impl From<serde_json::Error> for FormatError {
+    fn from(e: serde_json::Error) -> Self {
+        FormatError::Json(e)
+    }
+}
+
+impl From<MazeError> for FormatError {
+    fn from(e: MazeError) -> Self {
+        FormatError::Maze(e)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MazeDocument {
+    seed: Option<u64>,
+    start: [usize; 2],
+    exit: [usize; 2],
+    /// One string per row, `#` for walls and `.` for ground
+    tiles: Vec<String>,
+    rewards: Vec<RewardDocument>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RewardDocument {
+    pos: [usize; 2],
+    malus: bool,
+    #[serde(default)]
+    found: bool,
+}
+
+impl Maze {
+    pub fn export(&self, format: Format) -> String {
+        match format {
+            Format::Tmx => self.to_tmx(),
+            Format::Ascii => self.to_ascii(),
+            Format::Json => self.to_json(),
+        }
+    }
+
+    pub fn import(text: &str, format: Format) -> Result<Maze, FormatError> {
+        match format {
+            Format::Tmx => Maze::from_tmx(text),
+            Format::Ascii => Maze::from_ascii(text),
+            Format::Json => Maze::from_json(text),
+        }
+    }
+
+    pub fn to_tmx(&self) -> String {
+        tmx::write(self)
+    }
+
+    /// Reads a maze from a Tiled map, ignoring its rules
+    pub fn from_tmx(text: &str) -> Result<Maze, FormatError> {
+        let map = tiled::parse(text.as_bytes()).map_err(TmxError::from)?;
+        Ok(tmx::from_map(&map)?.0)
+    }
+
+    pub fn to_ascii(&self) -> String {
+        let mut rows = self.rows();
+
+        for reward in self.rewards() {
+            let marker = if reward.malus { 'X' } else { 'A' };
+            mark(&mut rows, *reward.pos(), marker);
+        }
+        mark(&mut rows, self.exit(), 'E');
+        mark(&mut rows, self.start(), 'S');
+
+        rows.iter().map(|row| format!("{}\n", row)).collect()
+    }
+
+    pub fn from_ascii(text: &str) -> Result<Maze, FormatError> {
+        let rows: Vec<_> = text.trim_end().lines().map(str::trim_end).collect();
+        let (mut maze, markers) = from_rows(&rows, "AXSE")?;
+        let (mut start, mut exit) = (None, None);
+
+        for (pos, marker) in markers {
+            match marker {
+                'A' => maze.add_reward(Reward::new(pos, false)),
+                'X' => maze.add_reward(Reward::new(pos, true)),
+                'S' if start.is_none() => start = Some(pos),
+                'E' if exit.is_none() => exit = Some(pos),
+                _ => {
+                    return Err(FormatError::Invalid(format!(
+                        "more than one {} in the grid",
+                        marker
+                    )))
+                }
+            }
+        }
+
+        let missing = |marker| FormatError::Invalid(format!("no {} in the grid", marker));
+        maze.set_start(start.ok_or_else(|| missing('S'))?);
+        maze.set_exit(exit.ok_or_else(|| missing('E'))?);
+        checked(maze)
+    }
+
+    pub fn to_json(&self) -> String {
+        let document = MazeDocument {
+            seed: self.seed(),
+            start: [self.start().x, self.start().y],
+            exit: [self.exit().x, self.exit().y],
+            tiles: self.rows(),
+            rewards: self
+                .rewards()
+                .iter()
+                .map(|r| RewardDocument {
+                    pos: [r.pos().x, r.pos().y],
+                    malus: r.malus,
+                    found: r.found,
+                })
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&document).expect("a maze document always serializes")
+    }
+
+    pub fn from_json(text: &str) -> Result<Maze, FormatError> {
+        let document: MazeDocument = serde_json::from_str(text)?;
+        let rows: Vec<_> = document.tiles.iter().map(String::as_str).collect();
+        let (mut maze, _) = from_rows(&rows, "")?;
+
+        for reward in &document.rewards {
+            let pos = in_range(&maze, reward.pos)?;
+            let mut r = Reward::new(pos, reward.malus);
+            r.found = reward.found;
+            maze.add_reward(r);
+        }
+        maze.set_start(in_range(&maze, document.start)?);
+        maze.set_exit(in_range(&maze, document.exit)?);
+        maze.set_seed(document.seed);
+        checked(maze)
+    }
+
+    /// The tiles, one string per row, `#` for walls and `.` for ground
+    fn rows(&self) -> Vec<String> {
+        let (w, h) = self.dim();
+
+        (0..h)
+            .map(|y| {
+                (0..w)
+                    .map(|x| match self.get([x, y].into()) {
+                        Tile::Wall => '#',
+                        Tile::Ground => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn mark(rows: &mut [String], pos: CellIndex, marker: char) {
+    rows[pos.y].replace_range(pos.x..pos.x + 1, &marker.to_string());
+}
+
+/// Builds a maze from rows of `#` and `.`, other characters from `markers` being ground cells
+/// that are returned with their position
+fn from_rows(rows: &[&str], markers: &str) -> Result<(Maze, Vec<(CellIndex, char)>), FormatError> {
+    let w = rows.first().map_or(0, |row| row.chars().count());
+    if w < 3 || rows.len() < 3 {
+        return Err(FormatError::Invalid(
+            "the maze is smaller than 3x3".to_owned(),
+        ));
+    }
+
+    let mut maze = Maze::new((w, rows.len()));
+    let mut found = vec![];
+
+    for (y, row) in rows.iter().enumerate() {
+        if row.chars().count() != w {
+            return Err(FormatError::Invalid(format!("row {} is not {} wide", y, w)));
+        }
+
+        for (x, c) in row.chars().enumerate() {
+            let pos = CellIndex::from([x, y]);
+            match c {
+                '#' => continue,
+                '.' => {}
+                c if markers.contains(c) => found.push((pos, c)),
+                c => {
+                    return Err(FormatError::Invalid(format!(
+                        "unexpected {:?} at ({}, {})",
+                        c, x, y
+                    )))
+                }
+            }
+            maze.set(pos, Tile::Ground);
+        }
+    }
+
+    Ok((maze, found))
+}
+
+fn in_range(maze: &Maze, [x, y]: [usize; 2]) -> Result<CellIndex, FormatError> {
+    let pos = CellIndex::from([x, y]);
+
+    if maze.is_in_range(pos) {
+        Ok(pos)
+    } else {
+        Err(FormatError::Invalid(format!(
+            "({}, {}) is outside the maze",
+            x, y
+        )))
+    }
+}
+
+/// Makes sure that everything stands on the ground and can be reached
+fn checked(maze: Maze) -> Result<Maze, FormatError> {
+    let cells = maze
+        .rewards()
+        .iter()
+        .map(|r| *r.pos())
+        .chain(vec![maze.start(), maze.exit()]);
+
+    for pos in cells {
+        if maze.get(pos).is_wall() {
+            return Err(FormatError::Invalid(format!(
+                "({}, {}) is on a wall",
+                pos.x, pos.y
+            )));
+        }
+    }
+
+    maze.validate()?;
+    Ok(maze)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::Algorithm;
+
+    fn mazes() -> Vec<Maze> {
+        Algorithm::ALL
+            .iter()
+            .enumerate()
+            .map(|(seed, algorithm)| {
+                Maze::from_seed((21, 15), seed as u64, &*algorithm.generator()).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tmx_round_trip() {
+        for maze in mazes() {
+            assert_eq!(Maze::from_tmx(&maze.to_tmx()).unwrap(), maze);
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        for mut maze in mazes() {
+            let pos = *maze.rewards()[0].pos();
+            maze.get_mut_reward(pos).unwrap().found = true;
+            assert_eq!(Maze::from_json(&maze.to_json()).unwrap(), maze);
+        }
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        for mut maze in mazes() {
+            let text = maze.to_ascii();
+            maze.set_seed(None);
+            assert_eq!(Maze::from_ascii(&text).unwrap(), maze);
+        }
+    }
+
+    #[test]
+    fn shared_cells_are_refused() {
+        let mut maze = mazes().remove(0);
+        let start = maze.start();
+        maze.add_reward(Reward::new(start, false));
+
+        assert!(matches!(
+            Maze::from_json(&maze.to_json()),
+            Err(FormatError::Maze(MazeError::Overlapping(pos))) if pos == start
+        ));
+    }
+}
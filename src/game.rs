@@ -0,0 +1,522 @@
+use serde::{Deserialize, Serialize};
+
+use crate::enemy::{self, Enemy};
+use crate::maze::{CellIndex, Direction, Maze, REWARDS};
+use crate::player::{Player, PlayerState};
+use crate::score::Score;
+use crate::solver;
+use crate::vision::Vision;
+
+/// The simulation always advances by fixed steps of `1 / TICKS_PER_SECOND` seconds
+pub const TICKS_PER_SECOND: u32 = 50;
+
+/// How long the death animation plays before the game is lost
+const DEATH_TICKS: u64 = 40;
+/// Health lost when stepping on a malus
+const MALUS_DAMAGE: u8 = 1;
+/// Health lost when caught by an enemy
+const ENEMY_DAMAGE: u8 = 1;
+/// Enemies catch the player when closer than this, in cells, along both axes together
+const CONTACT_DISTANCE: f32 = 0.6;
+/// Number of steps of the best route shown by a hint
+pub const HINT_STEPS: usize = 8;
+/// How long a hint stays shown, in ticks
+const HINT_TICKS: u64 = 3 * TICKS_PER_SECOND as u64;
+
+/// What it takes to win a game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    /// How long the maze is shown before the fog falls, in ticks
+    pub preview_ticks: u64,
+    /// Number of rewards to collect before the exit opens
+    pub reward_quota: u8,
+    /// How long the player has once the fog fell, in ticks
+    pub time_limit: Option<u64>,
+    /// Health the player starts with, each malus and enemy bite takes one
+    pub health: u8,
+    /// Number of enemies roaming the maze
+    #[serde(default)]
+    pub enemies: u8,
+    /// How far the player sees under the fog, in cells
+    #[serde(default = "default_view_radius")]
+    pub view_radius: u8,
+}
+
+fn default_view_radius() -> u8 {
+    Rules::default().view_radius
+}
+
+impl Rules {
+    /// Default rules, asking for every reward of `maze`
+    pub fn for_maze(maze: &Maze) -> Rules {
+        let apples = maze.rewards().iter().filter(|r| !r.malus).count();
+
+        Rules {
+            reward_quota: apples.min(u8::MAX as usize) as u8,
+            ..Rules::default()
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            preview_ticks: 10 * TICKS_PER_SECOND as u64,
+            reward_quota: REWARDS as u8,
+            time_limit: None,
+            health: 3,
+            enemies: 0,
+            view_radius: 3,
+        }
+    }
+}
+
+/// An abstract input, independent from the device it comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Command {
+    Move(Direction),
+    /// Shows the next steps of the best route, at a cost
+    Hint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+/// Something that happened during a tick, used by the frontend to play sounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    FogFell,
+    Stepped,
+    RewardFound,
+    /// The player stepped on a malus and got hurt
+    MalusFound,
+    /// An enemy caught the player
+    EnemyHit,
+    /// The next steps of the best route are shown
+    HintShown,
+    Ended(Outcome),
+}
+
+/// The whole game logic, without any rendering, audio or input device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Game {
+    maze: Maze,
+    rules: Rules,
+    player: Player,
+    #[serde(default)]
+    enemies: Vec<Enemy>,
+    #[serde(default)]
+    vision: Vision,
+    /// Next steps of the best route and the tick they are hidden again, while shown
+    #[serde(default)]
+    hint: Option<(Vec<CellIndex>, u64)>,
+    tick: u64,
+    found: u8,
+    score: Score,
+    dead_since: Option<u64>,
+    outcome: Option<Outcome>,
+}
+
+impl Game {
+    pub fn new(maze: Maze, rules: Rules) -> Game {
+        Game {
+            player: Player::new(maze.start(), rules.health),
+            score: Score::new(&maze, &rules),
+            enemies: enemy::spawn(&maze, rules.enemies as usize),
+            vision: Vision::new(&maze),
+            hint: None,
+            maze,
+            rules,
+            tick: 0,
+            found: 0,
+            dead_since: None,
+            outcome: None,
+        }
+    }
+
+    pub fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    /// What the player sees and saw under the fog
+    pub fn vision(&self) -> &Vision {
+        &self.vision
+    }
+
+    /// Cells of the hint being shown, next one first
+    pub fn hint(&self) -> Option<&[CellIndex]> {
+        self.hint.as_ref().map(|(cells, _)| cells.as_slice())
+    }
+
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    pub fn tick_count(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn found(&self) -> u8 {
+        self.found
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// Whether everything is inside the maze, which a deserialized game may not ensure
+    pub fn is_consistent(&self) -> bool {
+        self.maze.is_consistent()
+            && self.player.cells().all(|cell| self.maze.is_in_range(cell))
+            && self
+                .enemies
+                .iter()
+                .flat_map(Enemy::cells)
+                .all(|cell| self.maze.is_in_range(cell))
+            && self.score.fits(&self.maze)
+    }
+
+    /// Whether the preview is over and the maze is covered by the fog
+    pub fn is_hidden(&self) -> bool {
+        self.tick >= self.rules.preview_ticks
+    }
+
+    /// Whether the cell towards `dir` from where the player is going can be walked to
+    pub fn is_open(&self, dir: Direction) -> bool {
+        self.open_neighbour(dir).is_some()
+    }
+
+    /// Whether a move given now would be taken, the player being neither walking, hurt nor dead
+    pub fn can_move(&self) -> bool {
+        !self.player.is_moving()
+            && matches!(self.player.state(), PlayerState::Idle | PlayerState::Run)
+    }
+
+    /// Whole seconds left before the fog falls, rounded up
+    pub fn preview_remaining_secs(&self) -> Option<u64> {
+        secs_left(self.tick, self.rules.preview_ticks)
+    }
+
+    /// Whole seconds left before the time runs out, rounded up, if there is a time limit
+    pub fn time_remaining_secs(&self) -> Option<u64> {
+        let limit = self.rules.time_limit?;
+        Some(secs_left(self.tick, self.rules.preview_ticks + limit).unwrap_or(0))
+    }
+
+    /// Advances the game by one tick, applying the given command
+    pub fn tick(&mut self, command: Option<Command>) -> Vec<Event> {
+        let mut events = vec![];
+
+        if self.outcome.is_some() {
+            return events;
+        }
+
+        if let Some(since) = self.dead_since {
+            if self.tick - since >= DEATH_TICKS {
+                self.end(Outcome::Lost, &mut events);
+                return events;
+            }
+        }
+
+        self.tick += 1;
+
+        if self.tick == self.rules.preview_ticks {
+            events.push(Event::FogFell);
+        }
+        if !self.is_hidden() {
+            return events;
+        }
+
+        if let Some(limit) = self.rules.time_limit {
+            if self.tick >= self.rules.preview_ticks + limit && self.dead_since.is_none() {
+                self.end(Outcome::Lost, &mut events);
+                return events;
+            }
+        }
+
+        let cell = self.player.cell();
+
+        if let Some(reward) = self.maze.get_mut_reward(cell) {
+            if reward.malus {
+                // a malus stays harmful once found, but spares an invulnerable player
+                if self.player.hurt(MALUS_DAMAGE) {
+                    reward.found = true;
+                    events.push(Event::MalusFound);
+
+                    if self.player.is_dead() {
+                        self.dead_since = Some(self.tick);
+                    }
+                }
+            } else if !reward.found {
+                reward.found = true;
+                self.found += 1;
+                self.score.reward_found(self.tick);
+                events.push(Event::RewardFound);
+            }
+        }
+
+        if self.dead_since.is_none()
+            && self.found >= self.rules.reward_quota
+            && cell == self.maze.exit()
+        {
+            self.score.won(self.tick - self.rules.preview_ticks);
+            self.end(Outcome::Won, &mut events);
+            return events;
+        }
+
+        if self
+            .hint
+            .as_ref()
+            .is_some_and(|(_, until)| self.tick >= *until)
+        {
+            self.hint = None;
+        }
+        match command {
+            Some(Command::Move(dir)) => self.try_move(dir, &mut events),
+            Some(Command::Hint) => self.show_hint(&mut events),
+            None => {}
+        }
+        self.player.update();
+        self.vision.update(
+            &self.maze,
+            self.player.sight_cells(),
+            self.rules.view_radius,
+        );
+        self.update_enemies(&mut events);
+
+        events
+    }
+
+    fn update_enemies(&mut self, events: &mut Vec<Event>) {
+        let (x, y) = self.player.pos();
+        let mut caught = false;
+
+        for enemy in &mut self.enemies {
+            enemy.update(&self.maze, self.player.cell());
+
+            let (ex, ey) = enemy.pos();
+            caught |= (ex - x).abs() + (ey - y).abs() < CONTACT_DISTANCE;
+        }
+
+        if caught && self.player.hurt(ENEMY_DAMAGE) {
+            events.push(Event::EnemyHit);
+            if self.player.is_dead() {
+                self.dead_since = Some(self.tick);
+            }
+        }
+    }
+
+    fn try_move(&mut self, dir: Direction, events: &mut Vec<Event>) {
+        if self.player.is_dead() {
+            return;
+        }
+
+        let next = match self.open_neighbour(dir) {
+            Some(next) => next,
+            None => return,
+        };
+
+        // the player only turns around when the move is taken
+        if self.player.translate(dir) {
+            match dir {
+                Direction::West => self.player.set_flipped(true),
+                Direction::East => self.player.set_flipped(false),
+                _ => {}
+            }
+            self.score.stepped(&self.maze, next);
+            events.push(Event::Stepped);
+        }
+    }
+
+    fn open_neighbour(&self, dir: Direction) -> Option<CellIndex> {
+        self.maze
+            .neighbour(self.player.destination(), dir)
+            .filter(|next| !self.maze.get(*next).is_wall())
+    }
+
+    /// Shows the way from where the player is going, unless a hint is already shown
+    fn show_hint(&mut self, events: &mut Vec<Event>) {
+        if self.hint.is_some() || self.player.is_dead() {
+            return;
+        }
+
+        let from = self.player.destination();
+        let quota = self.rules.reward_quota.saturating_sub(self.found);
+        if let Some(mut route) = solver::route(&self.maze, from, quota as usize) {
+            route.truncate(HINT_STEPS);
+            self.hint = Some((route, self.tick + HINT_TICKS));
+            self.score.hint_used();
+            events.push(Event::HintShown);
+        }
+    }
+
+    fn end(&mut self, outcome: Outcome, events: &mut Vec<Event>) {
+        self.outcome = Some(outcome);
+        events.push(Event::Ended(outcome));
+    }
+}
+
+/// Whole seconds between `tick` and `end`, rounded up, `None` once `end` is reached
+fn secs_left(tick: u64, end: u64) -> Option<u64> {
+    if tick < end {
+        let ticks_per_second = TICKS_PER_SECOND as u64;
+        Some((end - tick).div_ceil(ticks_per_second))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::STEP_TICKS;
+    use crate::rewards::Reward;
+    use crate::tile::Tile;
+
+    /// A corridor from the start at (1, 1) to the exit at (6, 1), with an apple in a recess below
+    /// (3, 1)
+    fn corridor() -> Maze {
+        let mut maze = Maze::new((7, 4));
+        for x in 1..7 {
+            maze.set([x, 1].into(), Tile::Ground);
+        }
+        maze.set([3, 2].into(), Tile::Ground);
+        maze.set_exit([6, 1].into());
+        maze.add_reward(Reward::new([3, 2].into(), false));
+        maze
+    }
+
+    fn rules() -> Rules {
+        Rules {
+            preview_ticks: 1,
+            reward_quota: 1,
+            ..Rules::default()
+        }
+    }
+
+    /// Gives `command` for `ticks` ticks, which walks as many cells as whole steps fit
+    fn hold(game: &mut Game, command: Option<Command>, ticks: u32) -> Vec<Event> {
+        (0..ticks).flat_map(|_| game.tick(command)).collect()
+    }
+
+    fn walk(game: &mut Game, dir: Direction, steps: u32) -> Vec<Event> {
+        hold(game, Some(Command::Move(dir)), steps * STEP_TICKS)
+    }
+
+    #[test]
+    fn fog_falls_at_the_end_of_the_preview() {
+        let rules = Rules {
+            preview_ticks: 5,
+            ..rules()
+        };
+        let mut game = Game::new(corridor(), rules);
+
+        assert!(hold(&mut game, None, 4).is_empty());
+        assert!(!game.is_hidden());
+        assert_eq!(game.tick(None), vec![Event::FogFell]);
+        assert!(game.is_hidden());
+        assert!(hold(&mut game, None, 10).is_empty());
+    }
+
+    #[test]
+    fn rewards_are_counted_once() {
+        let rules = Rules {
+            reward_quota: 2,
+            ..rules()
+        };
+        let mut game = Game::new(corridor(), rules);
+
+        let mut events = walk(&mut game, Direction::East, 2);
+        events.extend(walk(&mut game, Direction::South, 1));
+        events.extend(hold(&mut game, None, 20));
+        events.extend(walk(&mut game, Direction::North, 1));
+        events.extend(walk(&mut game, Direction::South, 1));
+        events.extend(hold(&mut game, None, 1));
+
+        let found = events.iter().filter(|e| **e == Event::RewardFound);
+        assert_eq!(found.count(), 1);
+        assert_eq!(game.found(), 1);
+    }
+
+    #[test]
+    fn exit_only_wins_once_the_quota_is_met() {
+        let mut game = Game::new(corridor(), rules());
+
+        walk(&mut game, Direction::East, 5);
+        hold(&mut game, None, 20);
+        assert_eq!(game.player().cell(), game.maze().exit());
+        assert_eq!(game.outcome(), None);
+
+        walk(&mut game, Direction::West, 3);
+        walk(&mut game, Direction::South, 1);
+        walk(&mut game, Direction::North, 1);
+        walk(&mut game, Direction::East, 3);
+        assert_eq!(game.tick(None), vec![Event::Ended(Outcome::Won)]);
+        assert_eq!(game.outcome(), Some(Outcome::Won));
+        assert!(game.tick(None).is_empty());
+    }
+
+    #[test]
+    fn cells_left_behind_go_out_of_sight() {
+        let rules = Rules {
+            view_radius: 1,
+            ..rules()
+        };
+        let mut game = Game::new(corridor(), rules);
+
+        walk(&mut game, Direction::East, 2);
+        let start = game.maze().start();
+        assert!(!game.vision().is_visible(game.maze(), start));
+        assert!(game.vision().is_explored(game.maze(), start));
+        assert!(game.vision().is_visible(game.maze(), [4, 1].into()));
+    }
+
+    #[test]
+    fn dying_on_the_exit_loses() {
+        let mut maze = corridor();
+        maze.add_reward(Reward::new(maze.exit(), true));
+        let rules = Rules {
+            reward_quota: 0,
+            health: 1,
+            ..rules()
+        };
+        let mut game = Game::new(maze, rules);
+
+        walk(&mut game, Direction::East, 5);
+        let events = hold(&mut game, None, DEATH_TICKS as u32 + 2);
+        assert_eq!(events.first(), Some(&Event::MalusFound));
+        assert_eq!(events.last(), Some(&Event::Ended(Outcome::Lost)));
+        assert_eq!(game.outcome(), Some(Outcome::Lost));
+    }
+
+    #[test]
+    fn running_out_of_time_loses() {
+        let rules = Rules {
+            preview_ticks: 5,
+            time_limit: Some(10),
+            ..rules()
+        };
+        let mut game = Game::new(corridor(), rules);
+
+        hold(&mut game, None, 14);
+        assert_eq!(game.time_remaining_secs(), Some(1));
+        assert_eq!(game.outcome(), None);
+        assert_eq!(game.tick(None), vec![Event::Ended(Outcome::Lost)]);
+        assert_eq!(game.tick_count(), 15);
+    }
+}
@@ -0,0 +1,154 @@
+//! Gamepads, read through the gilrs events of ggez. The d-pad and the left stick move, the other
+//! buttons stand for the actions of the keyboard controls, or for keys in the menus, so the game
+//! logic never knows where an input came from. Gamepads may come and go while the game runs.
+
+use std::collections::HashMap;
+
+use ggez::event::{Axis, Button, GamepadId};
+use ggez::input::gamepad;
+use ggez::input::keyboard::KeyCode;
+use ggez::Context;
+
+use crate::controls::Action;
+use crate::maze::Direction;
+
+/// Sticks closer to the center than this, from 0 to 1, are at rest
+pub const STICK_DEADZONE: f32 = 0.35;
+
+/// The action of a button, in game
+pub fn action(button: Button) -> Option<Action> {
+    match button {
+        Button::Start => Some(Action::Pause),
+        Button::Select => Some(Action::Restart),
+        Button::West => Some(Action::Hint),
+        Button::East => Some(Action::ToggleGhost),
+        Button::North => Some(Action::ToggleMinimap),
+        Button::LeftTrigger => Some(Action::ZoomOut),
+        Button::RightTrigger => Some(Action::ZoomIn),
+        _ => None,
+    }
+}
+
+/// The key a button stands for, in the menus
+pub fn menu_key(button: Button) -> Option<KeyCode> {
+    match button {
+        Button::DPadUp => Some(KeyCode::Up),
+        Button::DPadDown => Some(KeyCode::Down),
+        Button::DPadLeft => Some(KeyCode::Left),
+        Button::DPadRight => Some(KeyCode::Right),
+        Button::South | Button::Start => Some(KeyCode::Return),
+        Button::East | Button::Select => Some(KeyCode::Escape),
+        _ => None,
+    }
+}
+
+/// Where a d-pad button walks
+pub fn dpad_direction(button: Button) -> Option<Direction> {
+    match button {
+        Button::DPadUp => Some(Direction::North),
+        Button::DPadRight => Some(Direction::East),
+        Button::DPadDown => Some(Direction::South),
+        Button::DPadLeft => Some(Direction::West),
+        _ => None,
+    }
+}
+
+fn dpad_button(direction: Direction) -> Button {
+    match direction {
+        Direction::North => Button::DPadUp,
+        Direction::East => Button::DPadRight,
+        Direction::South => Button::DPadDown,
+        Direction::West => Button::DPadLeft,
+    }
+}
+
+/// Where a stick points, if out of the deadzone, `y` going up
+fn stick_direction((x, y): (f32, f32)) -> Option<Direction> {
+    if x * x + y * y < STICK_DEADZONE * STICK_DEADZONE {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x > 0. {
+            Direction::East
+        } else {
+            Direction::West
+        })
+    } else {
+        Some(if y > 0. {
+            Direction::North
+        } else {
+            Direction::South
+        })
+    }
+}
+
+/// What is held on one gamepad
+#[derive(Debug, Clone, Default)]
+struct Pad {
+    /// D-pad buttons held, the latest last
+    dpad: Vec<Direction>,
+    /// D-pads reported as axes by some gamepads
+    dpad_axes: (f32, f32),
+    left_stick: (f32, f32),
+}
+
+impl Pad {
+    fn direction(&self) -> Option<Direction> {
+        self.dpad
+            .last()
+            .copied()
+            .or_else(|| stick_direction(self.dpad_axes))
+            .or_else(|| stick_direction(self.left_stick))
+    }
+}
+
+/// Directions held on every gamepad used so far
+#[derive(Debug, Clone, Default)]
+pub struct Gamepads {
+    pads: HashMap<GamepadId, Pad>,
+}
+
+impl Gamepads {
+    pub fn button_down(&mut self, id: GamepadId, button: Button) {
+        if let Some(direction) = dpad_direction(button) {
+            let dpad = &mut self.pads.entry(id).or_default().dpad;
+            dpad.retain(|held| *held != direction);
+            dpad.push(direction);
+        }
+    }
+
+    pub fn button_up(&mut self, id: GamepadId, button: Button) {
+        if let (Some(direction), Some(pad)) = (dpad_direction(button), self.pads.get_mut(&id)) {
+            pad.dpad.retain(|held| *held != direction);
+        }
+    }
+
+    /// Records a stick move, returns the d-pad button it stands for when it just left the
+    /// deadzone or changed direction, to browse menus with it
+    pub fn axis(&mut self, id: GamepadId, axis: Axis, value: f32) -> Option<Button> {
+        let pad = self.pads.entry(id).or_default();
+        let before = pad.direction();
+        match axis {
+            Axis::LeftStickX => pad.left_stick.0 = value,
+            Axis::LeftStickY => pad.left_stick.1 = value,
+            Axis::DPadX => pad.dpad_axes.0 = value,
+            Axis::DPadY => pad.dpad_axes.1 = value,
+            _ => return None,
+        }
+
+        let after = pad.direction();
+        after.filter(|_| after != before).map(dpad_button)
+    }
+
+    /// Where a gamepad points, if any does
+    pub fn direction(&self) -> Option<Direction> {
+        self.pads.values().find_map(Pad::direction)
+    }
+
+    /// Forgets the gamepads unplugged since the last call, returns whether there were some
+    pub fn remove_disconnected(&mut self, ctx: &Context) -> bool {
+        let count = self.pads.len();
+        self.pads
+            .retain(|id, _| gamepad::gamepad(ctx, *id).is_connected());
+        self.pads.len() < count
+    }
+}
@@ -0,0 +1,32 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::{Grid, MazeGenerator};
+use crate::maze::{Maze, MazeRng};
+
+/// Aldous-Broder: a plain random walk that links every cell it enters for the first time
+pub struct AldousBroder;
+
+impl MazeGenerator for AldousBroder {
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng) {
+        let mut grid = Grid::new(maze);
+        let mut visited = vec![false; grid.cell_count()];
+
+        let mut curr = grid.cell(rng.gen_range(0, grid.cell_count() as u32) as usize);
+        let mut remaining = grid.cell_count() - 1;
+        visited[grid.index(curr)] = true;
+        grid.open(curr);
+
+        while remaining > 0 {
+            let neighbours: Vec<_> = grid.neighbours(curr).map(|(_, n)| n).collect();
+            let next = *neighbours.choose(rng).unwrap();
+
+            if !visited[grid.index(next)] {
+                visited[grid.index(next)] = true;
+                grid.link(curr, next);
+                remaining -= 1;
+            }
+            curr = next;
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use rand::seq::SliceRandom;
+
+use super::{Grid, MazeGenerator};
+use crate::maze::{Maze, MazeRng};
+
+/// Randomized depth-first search: long winding corridors with few branches
+pub struct Backtracker;
+
+impl MazeGenerator for Backtracker {
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng) {
+        let mut grid = Grid::new(maze);
+        let mut visited = vec![false; grid.cell_count()];
+
+        // an explicit stack, recursion would overflow on big mazes
+        let mut stack = vec![(0, 0)];
+        visited[0] = true;
+        grid.open((0, 0));
+
+        while let Some(&curr) = stack.last() {
+            let unvisited: Vec<_> = grid
+                .neighbours(curr)
+                .map(|(_, n)| n)
+                .filter(|&n| !visited[grid.index(n)])
+                .collect();
+
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    visited[grid.index(next)] = true;
+                    grid.link(curr, next);
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
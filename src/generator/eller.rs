@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::{Grid, MazeGenerator, UnionFind};
+use crate::maze::{Maze, MazeRng};
+
+/// Eller's algorithm: carves one row at a time, only remembering which cells of the row are
+/// already connected
+pub struct Eller;
+
+impl MazeGenerator for Eller {
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng) {
+        let mut grid = Grid::new(maze);
+        let mut sets = UnionFind::new(grid.cell_count());
+        let (w, h) = grid.dim;
+
+        for y in 0..h {
+            let last_row = y == h - 1;
+
+            for x in 0..w {
+                grid.open((x, y));
+            }
+
+            // join neighbours of different sets, all of them on the last row
+            for x in 0..w - 1 {
+                let (a, b) = ((x, y), (x + 1, y));
+                if sets.find(grid.index(a)) != sets.find(grid.index(b))
+                    && (last_row || rng.gen_bool(0.5))
+                {
+                    sets.union(grid.index(a), grid.index(b));
+                    grid.link(a, b);
+                }
+            }
+
+            if last_row {
+                break;
+            }
+
+            // every set goes down at least once, a `BTreeMap` keeps the order deterministic
+            let mut row_sets: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            for x in 0..w {
+                let root = sets.find(grid.index((x, y)));
+                row_sets.entry(root).or_default().push(x);
+            }
+
+            for mut members in row_sets.into_values() {
+                members.shuffle(rng);
+                for (i, x) in members.into_iter().enumerate() {
+                    if i == 0 || rng.gen_bool(0.5) {
+                        sets.union(grid.index((x, y)), grid.index((x, y + 1)));
+                        grid.link((x, y), (x, y + 1));
+                    }
+                }
+            }
+        }
+    }
+}
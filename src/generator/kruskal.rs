@@ -0,0 +1,81 @@
+use rand::seq::SliceRandom;
+
+use super::{Grid, MazeGenerator};
+use crate::maze::{Direction, Maze, MazeRng};
+
+/// Randomized Kruskal's algorithm: opens walls in random order unless it would create a loop
+pub struct Kruskal;
+
+impl MazeGenerator for Kruskal {
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng) {
+        let mut grid = Grid::new(maze);
+        let mut sets = UnionFind::new(grid.cell_count());
+
+        let mut edges: Vec<_> = grid
+            .cells()
+            .flat_map(|cell| {
+                [Direction::East, Direction::South]
+                    .iter()
+                    .filter_map(|&dir| grid.neighbour(cell, dir))
+                    .map(|n| (cell, n))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        edges.shuffle(rng);
+
+        for (a, b) in edges {
+            if sets.union(grid.index(a), grid.index(b)) {
+                grid.link(a, b);
+            }
+        }
+    }
+}
+
+/// Disjoint sets of indices, with path compression and union by rank
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+}
+
+impl UnionFind {
+    pub fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+        }
+    }
+
+    pub fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut curr = i;
+        while self.parents[curr] != root {
+            let next = self.parents[curr];
+            self.parents[curr] = root;
+            curr = next;
+        }
+        root
+    }
+
+    /// Merges the sets of `a` and `b`, returns false if they already were the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        match self.ranks[a].cmp(&self.ranks[b]) {
+            std::cmp::Ordering::Less => self.parents[a] = b,
+            std::cmp::Ordering::Greater => self.parents[b] = a,
+            std::cmp::Ordering::Equal => {
+                self.parents[b] = a;
+                self.ranks[a] += 1;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,264 @@
+//! Maze carving algorithms.
+//!
+//! Generators work on a grid of cells: cell `(x, y)` is the tile `(2x + 1, 2y + 1)` and two
+//! neighbouring cells are linked by opening the tile between them. Every generator carves a
+//! perfect maze, where any two cells are joined by exactly one path.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::maze::{CellIndex, Direction, Maze, MazeRng, DIRECTIONS};
+use crate::tile::Tile;
+
+mod aldous_broder;
+mod backtracker;
+mod eller;
+mod kruskal;
+mod prim;
+mod recursive_division;
+mod wilson;
+
+pub use aldous_broder::AldousBroder;
+pub use backtracker::Backtracker;
+pub use eller::Eller;
+pub use kruskal::{Kruskal, UnionFind};
+pub use prim::Prim;
+pub use recursive_division::RecursiveDivision;
+pub use wilson::Wilson;
+
+pub trait MazeGenerator {
+    /// Carves a perfect maze into `maze`, which must only contain walls
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng);
+}
+
+/// Position of a cell in the grid of odd tiles
+pub type Cell = (usize, usize);
+
+/// The available generators, selectable at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    #[default]
+    Backtracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    Eller,
+    RecursiveDivision,
+    AldousBroder,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 7] = [
+        Algorithm::Backtracker,
+        Algorithm::Prim,
+        Algorithm::Kruskal,
+        Algorithm::Wilson,
+        Algorithm::Eller,
+        Algorithm::RecursiveDivision,
+        Algorithm::AldousBroder,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Backtracker => "backtracker",
+            Algorithm::Prim => "prim",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Wilson => "wilson",
+            Algorithm::Eller => "eller",
+            Algorithm::RecursiveDivision => "division",
+            Algorithm::AldousBroder => "aldous-broder",
+        }
+    }
+
+    pub fn generator(self) -> Box<dyn MazeGenerator> {
+        match self {
+            Algorithm::Backtracker => Box::new(Backtracker),
+            Algorithm::Prim => Box::new(Prim),
+            Algorithm::Kruskal => Box::new(Kruskal),
+            Algorithm::Wilson => Box::new(Wilson),
+            Algorithm::Eller => Box::new(Eller),
+            Algorithm::RecursiveDivision => Box::new(RecursiveDivision),
+            Algorithm::AldousBroder => Box::new(AldousBroder),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Algorithm::ALL.iter().map(|a| a.name()).collect();
+                format!(
+                    "unknown algorithm {}, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl Serialize for Algorithm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Algorithm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Cell-level view over the tiles of a maze
+pub struct Grid<'a> {
+    maze: &'a mut Maze,
+    /// (width, height), in cells
+    pub dim: (usize, usize),
+}
+
+impl<'a> Grid<'a> {
+    pub fn new(maze: &'a mut Maze) -> Grid<'a> {
+        let (w, h) = maze.dim();
+        Grid {
+            maze,
+            dim: ((w - 1) / 2, (h - 1) / 2),
+        }
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.dim.0 * self.dim.1
+    }
+
+    pub fn index(&self, (x, y): Cell) -> usize {
+        y * self.dim.0 + x
+    }
+
+    pub fn cell(&self, index: usize) -> Cell {
+        (index % self.dim.0, index / self.dim.0)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Cell> {
+        let (w, h) = self.dim;
+        (0..h).flat_map(move |y| (0..w).map(move |x| (x, y)))
+    }
+
+    pub fn tile((x, y): Cell) -> CellIndex {
+        [x * 2 + 1, y * 2 + 1].into()
+    }
+
+    pub fn neighbour(&self, (x, y): Cell, dir: Direction) -> Option<Cell> {
+        let rel = dir.as_relative();
+        let (nx, ny) = (x as isize + rel.x, y as isize + rel.y);
+
+        if (0..self.dim.0 as isize).contains(&nx) && (0..self.dim.1 as isize).contains(&ny) {
+            Some((nx as usize, ny as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn neighbours(&self, cell: Cell) -> impl Iterator<Item = (Direction, Cell)> + '_ {
+        DIRECTIONS
+            .iter()
+            .filter_map(move |&dir| self.neighbour(cell, dir).map(|n| (dir, n)))
+    }
+
+    pub fn open(&mut self, cell: Cell) {
+        self.maze.set(Self::tile(cell), Tile::Ground);
+    }
+
+    pub fn is_open(&self, cell: Cell) -> bool {
+        !self.maze.get(Self::tile(cell)).is_wall()
+    }
+
+    /// Opens both cells and the wall between them, `a` and `b` must be neighbours
+    pub fn link(&mut self, a: Cell, b: Cell) {
+        self.open(a);
+        self.open(b);
+        self.maze.set(Self::between(a, b), Tile::Ground);
+    }
+
+    /// Closes the wall between two neighbouring cells
+    pub fn unlink(&mut self, a: Cell, b: Cell) {
+        self.maze.set(Self::between(a, b), Tile::Wall);
+    }
+
+    pub fn is_linked(&self, a: Cell, b: Cell) -> bool {
+        !self.maze.get(Self::between(a, b)).is_wall()
+    }
+
+    fn between(a: Cell, b: Cell) -> CellIndex {
+        [a.0 + b.0 + 1, a.1 + b.1 + 1].into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [(usize, usize); 3] = [(7, 7), (21, 21), (31, 15)];
+
+    #[test]
+    fn same_seed_same_maze() {
+        for algorithm in &Algorithm::ALL {
+            for &size in &SIZES {
+                let a = Maze::from_seed(size, 42, &*algorithm.generator()).unwrap();
+                let b = Maze::from_seed(size, 42, &*algorithm.generator()).unwrap();
+                assert_eq!(a, b, "{} {:?}", algorithm, size);
+            }
+        }
+    }
+
+    #[test]
+    fn seeds_give_different_mazes() {
+        for algorithm in &Algorithm::ALL {
+            let a = Maze::from_seed((21, 21), 1, &*algorithm.generator()).unwrap();
+            let b = Maze::from_seed((21, 21), 2, &*algorithm.generator()).unwrap();
+            assert_ne!(a, b, "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn mazes_are_perfect() {
+        for algorithm in &Algorithm::ALL {
+            for &size in &SIZES {
+                for seed in 0..20 {
+                    let maze = Maze::from_seed(size, seed, &*algorithm.generator()).unwrap();
+                    let context = format!("{} {:?} seed {}", algorithm, size, seed);
+                    assert_eq!(maze.validate(), Ok(()), "{}", context);
+
+                    // every cell is reached, by exactly one path
+                    let distances = maze.distances(maze.start(), |_| true);
+                    let mut copy = maze.clone();
+                    let grid = Grid::new(&mut copy);
+                    let (w, h) = grid.dim;
+                    let mut links = 0;
+                    for y in 0..h {
+                        for x in 0..w {
+                            let tile = CellIndex::from([2 * x + 1, 2 * y + 1]);
+                            assert!(distances[maze.index(tile)].is_some(), "{}", context);
+                            links += usize::from(x + 1 < w && grid.is_linked((x, y), (x + 1, y)));
+                            links += usize::from(y + 1 < h && grid.is_linked((x, y), (x, y + 1)));
+                        }
+                    }
+                    assert_eq!(links, w * h - 1, "{}", context);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use rand::Rng;
+
+use super::{Grid, MazeGenerator};
+use crate::maze::{Maze, MazeRng};
+
+/// Randomized Prim's algorithm: grows the maze from random frontier walls, many short branches
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng) {
+        let mut grid = Grid::new(maze);
+        let mut visited = vec![false; grid.cell_count()];
+
+        // walls between a visited cell and a possibly unvisited one
+        let mut frontier = vec![];
+
+        visited[0] = true;
+        grid.open((0, 0));
+        frontier.extend(grid.neighbours((0, 0)).map(|(_, n)| ((0, 0), n)));
+
+        while !frontier.is_empty() {
+            let (from, to) = frontier.swap_remove(rng.gen_range(0, frontier.len() as u32) as usize);
+
+            if visited[grid.index(to)] {
+                continue;
+            }
+
+            visited[grid.index(to)] = true;
+            grid.link(from, to);
+            frontier.extend(
+                grid.neighbours(to)
+                    .filter(|&(_, n)| !visited[grid.index(n)])
+                    .map(|(_, n)| (to, n)),
+            );
+        }
+    }
+}
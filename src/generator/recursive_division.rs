@@ -0,0 +1,63 @@
+use rand::Rng;
+
+use super::{Grid, MazeGenerator};
+use crate::maze::{Maze, MazeRng};
+
+/// Recursive division: starts from an empty room and splits it with walls that have a single
+/// gap, giving long straight walls
+pub struct RecursiveDivision;
+
+/// A rectangle of cells: (x, y, width, height)
+type Chamber = (usize, usize, usize, usize);
+
+impl MazeGenerator for RecursiveDivision {
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng) {
+        let mut grid = Grid::new(maze);
+        let (w, h) = grid.dim;
+
+        for (x, y) in grid.cells().collect::<Vec<_>>() {
+            if x + 1 < w {
+                grid.link((x, y), (x + 1, y));
+            }
+            if y + 1 < h {
+                grid.link((x, y), (x, y + 1));
+            }
+        }
+        if w * h == 1 {
+            grid.open((0, 0));
+        }
+
+        let mut chambers: Vec<Chamber> = vec![(0, 0, w, h)];
+
+        while let Some((x, y, w, h)) = chambers.pop() {
+            if w < 2 || h < 2 {
+                continue;
+            }
+
+            let horizontal = match w.cmp(&h) {
+                std::cmp::Ordering::Less => true,
+                std::cmp::Ordering::Greater => false,
+                std::cmp::Ordering::Equal => rng.gen_bool(0.5),
+            };
+
+            if horizontal {
+                // wall between rows `y + at` and `y + at + 1`
+                let at = rng.gen_range(0, h as u32 - 1) as usize;
+                let gap = x + rng.gen_range(0, w as u32) as usize;
+                for cx in (x..x + w).filter(|&cx| cx != gap) {
+                    grid.unlink((cx, y + at), (cx, y + at + 1));
+                }
+                chambers.push((x, y, w, at + 1));
+                chambers.push((x, y + at + 1, w, h - at - 1));
+            } else {
+                let at = rng.gen_range(0, w as u32 - 1) as usize;
+                let gap = y + rng.gen_range(0, h as u32) as usize;
+                for cy in (y..y + h).filter(|&cy| cy != gap) {
+                    grid.unlink((x + at, cy), (x + at + 1, cy));
+                }
+                chambers.push((x, y, at + 1, h));
+                chambers.push((x + at + 1, y, w - at - 1, h));
+            }
+        }
+    }
+}
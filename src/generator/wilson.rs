@@ -0,0 +1,40 @@
+use rand::seq::SliceRandom;
+
+use super::{Cell, Grid, MazeGenerator};
+use crate::maze::{Maze, MazeRng};
+
+/// Wilson's algorithm: loop-erased random walks, every spanning tree is equally likely
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn carve(&self, maze: &mut Maze, rng: &mut MazeRng) {
+        let mut grid = Grid::new(maze);
+        let mut in_tree = vec![false; grid.cell_count()];
+        // direction the last walk left each cell by, the loops erase themselves by overwriting
+        let mut next: Vec<Option<Cell>> = vec![None; grid.cell_count()];
+
+        in_tree[0] = true;
+        grid.open((0, 0));
+
+        let mut cells: Vec<_> = grid.cells().collect();
+        cells.shuffle(rng);
+
+        for start in cells {
+            let mut curr = start;
+            while !in_tree[grid.index(curr)] {
+                let neighbours: Vec<_> = grid.neighbours(curr).map(|(_, n)| n).collect();
+                let step = *neighbours.choose(rng).unwrap();
+                next[grid.index(curr)] = Some(step);
+                curr = step;
+            }
+
+            let mut curr = start;
+            while !in_tree[grid.index(curr)] {
+                let step = next[grid.index(curr)].unwrap();
+                in_tree[grid.index(curr)] = true;
+                grid.link(curr, step);
+                curr = step;
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Ghosts replay the best game won on a maze next to the player, so that they can race it. The
+//! best replays are kept in the user directory.
+
+use std::io::Write;
+
+use ggez::{filesystem, Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, Outcome};
+use crate::player::Player;
+use crate::replay::Replay;
+use crate::storage;
+
+/// Where the best replays are kept, in the user directory
+const GHOSTS_PATH: &str = "/ghosts.json";
+/// Bumped whenever the file layout changes, or the simulation so that recorded games would play
+/// differently
+const VERSION: u32 = 2;
+/// Number of mazes whose best replay is kept, the oldest ones are forgotten first
+const MAX_GHOSTS: usize = 50;
+
+/// The fastest won game of each maze played
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ghosts {
+    version: u32,
+    /// Most recently improved last
+    replays: Vec<Replay>,
+}
+
+impl Default for Ghosts {
+    fn default() -> Self {
+        Ghosts {
+            version: VERSION,
+            replays: vec![],
+        }
+    }
+}
+
+impl Ghosts {
+    /// Reads the saved replays, starting over when there are none or they cannot be used
+    pub fn load(ctx: &mut Context) -> Ghosts {
+        storage::load(ctx, GHOSTS_PATH, VERSION, "the ghosts").unwrap_or_default()
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let text = serde_json::to_string(self).expect("ghosts always serialize");
+        filesystem::create(ctx, GHOSTS_PATH)?.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    /// The best replay of the maze of `game`, if it was ever won
+    pub fn best(&self, game: &Game) -> Option<&Replay> {
+        self.replays.iter().find(|replay| replay.matches(game))
+    }
+
+    /// Keeps `replay` if it is a won game faster than the best one of its maze, returns whether
+    /// it was kept
+    pub fn offer(&mut self, replay: &Replay) -> bool {
+        if replay.outcome() != Some(Outcome::Won) {
+            return false;
+        }
+        let game = match replay.game() {
+            Ok(game) => game,
+            Err(_) => return false,
+        };
+
+        if let Some(i) = self.replays.iter().position(|r| r.matches(&game)) {
+            if self.replays[i].length() <= replay.length() {
+                return false;
+            }
+            self.replays.remove(i);
+        }
+        self.replays.push(replay.clone());
+        if self.replays.len() > MAX_GHOSTS {
+            self.replays.remove(0);
+        }
+        true
+    }
+}
+
+/// A recorded game played along the live one
+#[derive(Debug, Clone)]
+pub struct Ghost {
+    replay: Replay,
+    game: Game,
+}
+
+impl Ghost {
+    pub fn new(replay: Replay) -> Option<Ghost> {
+        let game = replay.game().ok()?;
+        Some(Ghost { replay, game })
+    }
+
+    /// Plays the recorded game up to `tick`
+    pub fn catch_up(&mut self, tick: u64) {
+        let tick = tick.min(self.replay.length());
+        while self.game.tick_count() < tick && self.game.outcome().is_none() {
+            self.tick();
+        }
+    }
+
+    /// Advances the recorded game by one tick, it stays where it ended afterwards
+    pub fn tick(&mut self) {
+        if self.game.tick_count() < self.replay.length() {
+            self.game
+                .tick(self.replay.command_at(self.game.tick_count()));
+        }
+    }
+
+    pub fn player(&self) -> &Player {
+        self.game.player()
+    }
+
+    /// Whether the ghost reached the exit
+    pub fn has_finished(&self) -> bool {
+        self.game.outcome().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Command, Rules};
+    use crate::generator::Algorithm;
+    use crate::maze::Maze;
+    use crate::pathfinding;
+    use crate::solver;
+
+    /// A game won along the shortest route, after waiting `idle` ticks under the fog
+    fn won_game(idle: u64) -> (Game, Replay) {
+        let maze = Maze::from_seed((15, 15), 7, &*Algorithm::Backtracker.generator()).unwrap();
+        let rules = Rules {
+            preview_ticks: 20,
+            ..Rules::for_maze(&maze)
+        };
+        let mut game = Game::new(maze, rules);
+        let mut replay = Replay::new(&game).unwrap();
+
+        let quota = game.rules().reward_quota as usize;
+        let route = solver::route(game.maze(), game.maze().start(), quota).unwrap();
+        let mut route = route.into_iter();
+        while game.outcome().is_none() {
+            let player = game.player();
+            let command = if game.is_hidden() && game.tick_count() >= 20 + idle && game.can_move() {
+                route.next().and_then(|next| {
+                    pathfinding::direction_to(game.maze(), player.cell(), next).map(Command::Move)
+                })
+            } else {
+                None
+            };
+            replay.record(game.tick_count(), command);
+            game.tick(command);
+        }
+        replay.finish(game.outcome().unwrap());
+        (game, replay)
+    }
+
+    #[test]
+    fn ghosts_follow_the_recorded_game() {
+        let (game, replay) = won_game(0);
+        assert_eq!(game.outcome(), Some(Outcome::Won));
+
+        let mut ghost = Ghost::new(replay.clone()).unwrap();
+        let mut live = replay.game().unwrap();
+        for _ in 0..replay.length() {
+            if live.outcome().is_none() {
+                live.tick(replay.command_at(live.tick_count()));
+            }
+            ghost.tick();
+            assert_eq!(ghost.player().pos(), live.player().pos());
+        }
+        assert!(ghost.has_finished());
+        assert_eq!(ghost.player().cell(), game.player().cell());
+
+        let mut late = Ghost::new(replay.clone()).unwrap();
+        late.catch_up(replay.length());
+        assert_eq!(late.player().pos(), ghost.player().pos());
+    }
+
+    #[test]
+    fn only_faster_games_are_kept() {
+        let (game, slow) = won_game(100);
+        let (_, fast) = won_game(0);
+        let mut ghosts = Ghosts::default();
+
+        assert!(ghosts.offer(&slow));
+        assert!(ghosts.offer(&fast));
+        assert!(!ghosts.offer(&slow));
+        assert_eq!(ghosts.best(&game).map(Replay::length), Some(fast.length()));
+    }
+}
@@ -0,0 +1,167 @@
+use ggez::graphics::{self, DrawMode, DrawParam, Image, Mesh, Rect, Scale, Text, TextFragment};
+use ggez::nalgebra::{Point2, Vector2};
+use ggez::{Context, GameResult};
+
+use crate::assets::Assets;
+use crate::game::Game;
+use crate::layout::{Layout, PANEL_WIDTH};
+
+pub const BACKGROUND_COLOR: [f32; 4] = [0.1, 0.2, 0.3, 1.0];
+
+/// Width of the health bar, in pixels
+const BAR_WIDTH: f32 = 90.;
+
+/// Side panel showing the health, the countdown, the rewards found, the score, the seed and the
+/// time left
+pub struct Hud {
+    /// (health, max health)
+    health: (u8, u8),
+    info: Text,
+    score_info: Text,
+    seed_info: Text,
+    /// Only shown when the game has a time limit
+    time_info: Option<Text>,
+}
+
+impl Hud {
+    pub fn new(game: &Game) -> Hud {
+        let seed = match game.maze().seed() {
+            Some(seed) => seed.to_string(),
+            None => "-".to_owned(),
+        };
+
+        Hud {
+            health: (game.player().health(), game.player().max_health()),
+            info: Text::new("10"),
+            score_info: score_text(0),
+            seed_info: Text::new(
+                TextFragment::new(format!("seed\n{}", seed)).scale(Scale::uniform(14.0)),
+            ),
+            time_info: None,
+        }
+    }
+
+    pub fn update(&mut self, game: &Game) {
+        self.health = (game.player().health(), game.player().max_health());
+        self.info = match game.preview_remaining_secs() {
+            Some(secs) => Text::new(format!("{:02}", secs)),
+            None => Text::new(format!("{}/{}", game.found(), game.rules().reward_quota)),
+        };
+        self.score_info = score_text(game.score().total());
+        self.time_info = game.time_remaining_secs().map(|secs| {
+            Text::new(TextFragment::new(format!("time\n{}s", secs)).scale(Scale::uniform(14.0)))
+        });
+    }
+
+    pub fn draw(&self, ctx: &mut Context, images: &Assets<Image>, layout: &Layout) -> GameResult {
+        // the maze may overflow its viewport, hide it behind the panel
+        let background = Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(layout.panel.x, layout.panel.y, PANEL_WIDTH, layout.window.1),
+            BACKGROUND_COLOR.into(),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::new())?;
+
+        let (health, max_health) = self.health;
+        let origin = layout.panel + Vector2::new(18., 12.);
+        draw_bar(ctx, images, "Back", origin, BAR_WIDTH)?;
+        if health > 0 {
+            let color = if u16::from(health) * 3 <= u16::from(max_health) {
+                "Red"
+            } else {
+                "Green"
+            };
+            let width = BAR_WIDTH * f32::from(health) / f32::from(max_health);
+            draw_bar(ctx, images, color, origin, width)?;
+        }
+
+        let origin = layout.panel + Vector2::new(18., 40.);
+        draw_panel(ctx, images, origin)?;
+        graphics::draw(
+            ctx,
+            &self.info,
+            DrawParam::new()
+                .dest(origin + Vector2::new(25., 10.))
+                .scale(Vector2::new(2.0, 2.0)),
+        )?;
+
+        let origin = origin + Vector2::new(0., 60.);
+        draw_panel(ctx, images, origin)?;
+        graphics::draw(
+            ctx,
+            &self.score_info,
+            DrawParam::new().dest(origin + Vector2::new(15., 10.)),
+        )?;
+
+        let origin = origin + Vector2::new(0., 60.);
+        draw_panel(ctx, images, origin)?;
+        graphics::draw(
+            ctx,
+            &self.seed_info,
+            DrawParam::new().dest(origin + Vector2::new(15., 10.)),
+        )?;
+
+        if let Some(time_info) = &self.time_info {
+            let origin = origin + Vector2::new(0., 60.);
+            draw_panel(ctx, images, origin)?;
+            graphics::draw(
+                ctx,
+                time_info,
+                DrawParam::new().dest(origin + Vector2::new(15., 10.)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn score_text(score: u32) -> Text {
+    Text::new(TextFragment::new(format!("score\n{}", score)).scale(Scale::uniform(14.0)))
+}
+
+/// Draws a 90x50 framed panel
+fn draw_panel(ctx: &mut Context, images: &Assets<Image>, dest: Point2<f32>) -> GameResult {
+    graphics::draw(
+        ctx,
+        &images["/ui/panel_brown.png"],
+        DrawParam::new().dest(dest).scale(Vector2::new(0.9, 0.5)),
+    )?;
+    graphics::draw(
+        ctx,
+        &images["/ui/panelInset_beige.png"],
+        DrawParam::new()
+            .dest(dest + Vector2::new(7.5, 7.5))
+            .scale(Vector2::new(0.8, 0.38)),
+    )
+}
+
+/// Draws a horizontal `ui/bar{color}` bar, at least as wide as its two 9px ends
+fn draw_bar(
+    ctx: &mut Context,
+    images: &Assets<Image>,
+    color: &str,
+    dest: Point2<f32>,
+    width: f32,
+) -> GameResult {
+    const END_WIDTH: f32 = 9.;
+    const MID_WIDTH: f32 = 18.;
+
+    let mid = (width - 2. * END_WIDTH).max(0.);
+    graphics::draw(
+        ctx,
+        &images[&format!("/ui/bar{}_horizontalLeft.png", color)],
+        DrawParam::new().dest(dest),
+    )?;
+    graphics::draw(
+        ctx,
+        &images[&format!("/ui/bar{}_horizontalMid.png", color)],
+        DrawParam::new()
+            .dest(dest + Vector2::new(END_WIDTH, 0.))
+            .scale(Vector2::new(mid / MID_WIDTH, 1.)),
+    )?;
+    graphics::draw(
+        ctx,
+        &images[&format!("/ui/bar{}_horizontalRight.png", color)],
+        DrawParam::new().dest(dest + Vector2::new(END_WIDTH + mid, 0.)),
+    )
+}
@@ -0,0 +1,140 @@
+//! Turns the directions held and pressed into one move per tick. The latest direction pressed
+//! wins over the others still held, and a direction pressed while the player is walking is kept
+//! for a while, so that it is taken as soon as the step ends, or at the first side corridor
+//! leading that way.
+
+use crate::game::Game;
+use crate::maze::{Direction, DIRECTIONS};
+use crate::player::STEP_TICKS;
+
+/// How long a pressed direction waits for the player to be able to take it, in ticks
+pub const BUFFER_TICKS: u32 = 2 * STEP_TICKS;
+
+#[derive(Debug, Clone, Default)]
+pub struct MoveInput {
+    /// Directions held, the latest pressed last
+    held: Vec<Direction>,
+    /// The latest direction pressed and the ticks it is still kept for
+    buffered: Option<(Direction, u32)>,
+}
+
+impl MoveInput {
+    pub fn press(&mut self, dir: Direction) {
+        self.held.retain(|held| *held != dir);
+        self.held.push(dir);
+        self.buffered = Some((dir, BUFFER_TICKS));
+    }
+
+    /// Forgets the directions released, and adds the ones held since before the first call
+    pub fn hold<F: Fn(Direction) -> bool>(&mut self, is_held: F) {
+        self.held.retain(|dir| is_held(*dir));
+        for dir in DIRECTIONS.iter().copied().filter(|dir| is_held(*dir)) {
+            if !self.held.contains(&dir) {
+                self.held.insert(0, dir);
+            }
+        }
+    }
+
+    /// Drops the direction waiting, when the player is sent somewhere else
+    pub fn cancel(&mut self) {
+        self.buffered = None;
+    }
+
+    /// Whether some direction is held or waiting
+    pub fn is_active(&self) -> bool {
+        !self.held.is_empty() || self.buffered.is_some()
+    }
+
+    /// The move for the next tick of `game`: the buffered direction, then the held ones from the
+    /// latest, the first one open from where the player is going being taken
+    pub fn next(&mut self, game: &Game) -> Option<Direction> {
+        let buffered = self.buffered.map(|(dir, _)| dir);
+        let dir = buffered
+            .into_iter()
+            .chain(self.held.iter().rev().copied())
+            .find(|dir| game.is_open(*dir));
+
+        self.buffered = match self.buffered {
+            Some(_) if game.can_move() && dir == buffered => None,
+            Some((dir, ticks)) if ticks > 1 => Some((dir, ticks - 1)),
+            _ => None,
+        };
+        dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Command, Rules};
+    use crate::maze::Maze;
+    use crate::tile::Tile;
+
+    /// A corridor from the start at (1, 1) going east, with a side corridor going south at (2, 1)
+    fn game() -> Game {
+        let mut maze = Maze::new((7, 5));
+        for x in 1..7 {
+            maze.set([x, 1].into(), Tile::Ground);
+        }
+        maze.set([2, 2].into(), Tile::Ground);
+        maze.set([2, 3].into(), Tile::Ground);
+        let rules = Rules {
+            preview_ticks: 1,
+            ..Rules::default()
+        };
+        Game::new(maze, rules)
+    }
+
+    /// Plays `ticks` ticks with the directions `held`
+    fn run(game: &mut Game, input: &mut MoveInput, held: &[Direction], ticks: u32) {
+        for _ in 0..ticks {
+            input.hold(|dir| held.contains(&dir));
+            let dir = input.next(game);
+            game.tick(dir.map(Command::Move));
+        }
+    }
+
+    #[test]
+    fn directions_pressed_mid_step_are_taken_once_it_ends() {
+        let mut game = game();
+        let mut input = MoveInput::default();
+
+        input.press(Direction::East);
+        run(&mut game, &mut input, &[Direction::East], STEP_TICKS / 2);
+        input.press(Direction::South);
+        run(&mut game, &mut input, &[], 3 * STEP_TICKS);
+
+        assert_eq!(game.player().cell(), [2, 2].into());
+        assert!(!input.is_active());
+    }
+
+    #[test]
+    fn latest_press_wins() {
+        let mut game = game();
+        let mut input = MoveInput::default();
+        input.press(Direction::East);
+        run(&mut game, &mut input, &[Direction::East], STEP_TICKS);
+        assert_eq!(game.player().cell(), [2, 1].into());
+
+        input.press(Direction::South);
+        assert_eq!(input.next(&game), Some(Direction::South));
+        input.cancel();
+        assert_eq!(input.next(&game), Some(Direction::South));
+        input.press(Direction::East);
+        assert_eq!(input.next(&game), Some(Direction::East));
+    }
+
+    #[test]
+    fn directions_pressed_early_turn_at_the_next_corner() {
+        let mut game = game();
+        let mut input = MoveInput::default();
+
+        input.press(Direction::East);
+        input.press(Direction::South);
+        run(&mut game, &mut input, &[Direction::East], 2 * STEP_TICKS);
+        assert_eq!(game.player().cell(), [2, 2].into());
+
+        run(&mut game, &mut input, &[Direction::East], 2 * STEP_TICKS);
+        assert_eq!(game.player().cell(), [2, 2].into());
+    }
+}
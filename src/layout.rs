@@ -0,0 +1,51 @@
+use ggez::graphics::Rect;
+use ggez::nalgebra::Point2;
+
+/// Size of a tile, in pixels, before scaling
+pub const TILE_SIZE: f32 = 32.;
+/// Width of the side panel on the right of the maze
+pub const PANEL_WIDTH: f32 = 128.;
+/// The window never grows past this size, bigger mazes scroll
+const MAX_WINDOW_SIZE: (f32, f32) = (1280., 960.);
+/// Room needed by the side panel and the menus
+const MIN_WINDOW_HEIGHT: f32 = 560.;
+/// Top of the minimap, below the panels of the HUD
+const MINIMAP_TOP: f32 = 290.;
+/// Largest side of the minimap, in pixels
+const MINIMAP_SIZE: f32 = 110.;
+
+/// Where everything goes on screen, computed from the maze dimensions and zoom
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    /// Area the maze is drawn in, in screen coordinates
+    pub maze: Rect,
+    /// Top-left corner of the side panel
+    pub panel: Point2<f32>,
+    /// Area the minimap is drawn in, inside the side panel
+    pub minimap: Rect,
+    /// (width, height) of the window
+    pub window: (f32, f32),
+}
+
+impl Layout {
+    pub fn new((w, h): (usize, usize), zoom: f32) -> Layout {
+        let maze = Rect::new(
+            0.,
+            0.,
+            (w as f32 * TILE_SIZE * zoom).min(MAX_WINDOW_SIZE.0 - PANEL_WIDTH),
+            (h as f32 * TILE_SIZE * zoom).min(MAX_WINDOW_SIZE.1),
+        );
+
+        Layout {
+            maze,
+            panel: Point2::new(maze.w, 0.),
+            minimap: Rect::new(
+                maze.w + (PANEL_WIDTH - MINIMAP_SIZE) / 2.,
+                MINIMAP_TOP,
+                MINIMAP_SIZE,
+                MINIMAP_SIZE,
+            ),
+            window: (maze.w + PANEL_WIDTH, (maze.h + 3.).max(MIN_WINDOW_HEIGHT)),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Best won games of each mode, kept in the user directory. A file that cannot be read, or was
+//! written by another version, is set aside and a new leaderboard is started.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ggez::{filesystem, Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, TICKS_PER_SECOND};
+use crate::storage;
+
+/// Where the leaderboard is kept, in the user directory
+const LEADERBOARD_PATH: &str = "/leaderboard.json";
+/// Bumped whenever the file layout changes
+const VERSION: u32 = 1;
+/// Number of entries kept in each table
+pub const TABLE_SIZE: usize = 10;
+
+/// A won game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+    /// Time spent under the fog, in seconds
+    pub time: f32,
+    pub steps: usize,
+    pub seed: Option<u64>,
+    /// (width, height), in tiles
+    pub size: (usize, usize),
+    /// When the game was won, in seconds since the Unix epoch
+    pub date: u64,
+}
+
+impl Entry {
+    pub fn new(name: &str, game: &Game) -> Entry {
+        let ticks = game.tick_count().saturating_sub(game.rules().preview_ticks);
+
+        Entry {
+            name: name.to_owned(),
+            score: game.score().total(),
+            time: ticks as f32 / TICKS_PER_SECOND as f32,
+            steps: game.score().steps(),
+            seed: game.maze().seed(),
+            size: game.maze().dim(),
+            date: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    /// The date as `YYYY-MM-DD`, in UTC
+    pub fn day(&self) -> String {
+        // days to civil date, from http://howardhinnant.github.io/date_algorithms.html
+        let days = (self.date / 86400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+/// The top entries of every mode, by table name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leaderboard {
+    version: u32,
+    tables: BTreeMap<String, Vec<Entry>>,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Leaderboard {
+            version: VERSION,
+            tables: BTreeMap::new(),
+        }
+    }
+}
+
+impl Leaderboard {
+    /// Reads the saved leaderboard, starting a new one when there is none or it cannot be used
+    pub fn load(ctx: &mut Context) -> Leaderboard {
+        storage::load(ctx, LEADERBOARD_PATH, VERSION, "the leaderboard").unwrap_or_default()
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let text = serde_json::to_string_pretty(self).expect("a leaderboard always serializes");
+        filesystem::create(ctx, LEADERBOARD_PATH)?.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    /// Adds `entry` to `table`, returns its rank, from 0, if it made it to the top
+    pub fn add(&mut self, table: &str, entry: Entry) -> Option<usize> {
+        let entries = self.tables.entry(table.to_owned()).or_default();
+        // ties go to the earlier game
+        let rank = entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(entries.len());
+
+        entries.insert(rank, entry);
+        entries.truncate(TABLE_SIZE);
+        Some(rank).filter(|rank| *rank < TABLE_SIZE)
+    }
+
+    /// Entries of `table`, best first
+    pub fn table(&self, table: &str) -> &[Entry] {
+        self.tables.get(table).map_or(&[], Vec::as_slice)
+    }
+}
@@ -0,0 +1,36 @@
+//! The game logic (`game`, `maze`, `player`, ...) never touches a window, a GPU or an
+//! audio device, so it can run headless. `render`, `scenes`, `assets` and `errors` hold the ggez
+//! side.
+
+pub mod assets;
+pub mod camera;
+pub mod campaign;
+pub mod controls;
+pub mod enemy;
+mod errors;
+pub mod formats;
+pub mod game;
+pub mod gamepad;
+pub mod generator;
+pub mod ghost;
+pub mod hud;
+pub mod input;
+pub mod layout;
+pub mod leaderboard;
+pub mod maze;
+pub mod menu;
+pub mod minimap;
+pub mod options;
+pub mod pathfinding;
+pub mod player;
+pub mod render;
+pub mod replay;
+pub mod rewards;
+pub mod save;
+pub mod scenes;
+pub mod score;
+pub mod solver;
+pub mod storage;
+pub mod tile;
+pub mod tmx;
+pub mod vision;
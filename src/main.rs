@@ -1,271 +1,32 @@
-use std::collections::HashMap;
-use std::time::{Duration, Instant};
-use std::{env, path};
+use std::{env, fs, path};
 
-use ggez::audio::{SoundData, SoundSource, Source};
+use ggez::audio::SoundData;
 use ggez::conf::{FullscreenType, NumSamples, WindowMode, WindowSetup};
-use ggez::event::{self, EventHandler};
-use ggez::graphics::{
-    self, Color, DrawMode, DrawParam, FillOptions, Image, MeshBuilder, Rect, Text,
-};
-use ggez::input::keyboard::{self, KeyCode};
-use ggez::nalgebra as na;
-use ggez::{Context, ContextBuilder, GameResult};
-use rand;
+use ggez::event;
+use ggez::graphics::Image;
+use ggez::{ContextBuilder, GameResult};
+
+use pate2crabe::assets::Assets;
+use pate2crabe::campaign::{Campaign, Progress};
+use pate2crabe::controls::Controls;
+use pate2crabe::formats::Format;
+use pate2crabe::ghost::Ghosts;
+use pate2crabe::layout::Layout;
+use pate2crabe::leaderboard::Leaderboard;
+use pate2crabe::options::Options;
+use pate2crabe::replay::Replay;
+use pate2crabe::save::SaveGame;
+use pate2crabe::scenes::{self, Mode, Replaying, Scene, SceneStack, Shared, Title};
 
-use crate::assets::Assets;
-use crate::maze::Maze;
-use crate::player::{Animation, Player, PlayerState};
-
-mod assets;
-mod maze;
-mod player;
-mod rewards;
-mod tile;
-
-struct MainState<'a> {
-    maze: Maze,
-    player: Player<'a>,
-    info: Text,
-    start: Instant,
-    found: u8,
-    images: &'a Assets<Image>,
-    sounds: &'a Assets<SoundData>,
-    hidden: bool,
-    dead_since: Option<Instant>,
-    death_sound: Source
-}
-
-impl<'a> MainState<'a> {
-    fn new(
-        ctx: &mut Context,
-        images: &'a Assets<Image>,
-        sounds: &'a Assets<SoundData>,
-    ) -> GameResult<MainState<'a>> {
-        let mut maze = Maze::new((21, 21), images);
-        maze.generate(&mut rand::thread_rng(), &images);
-
-        let mut player_animations = HashMap::new();
-        player_animations.insert(
-            PlayerState::Idle,
-            Animation::new(
-                images.get_from_pattern("game/idle_*.png"),
-                Duration::from_millis(150),
-            ),
-        );
-        player_animations.insert(
-            PlayerState::Run,
-            Animation::new(
-                images.get_from_pattern("game/run_*.png"),
-                Duration::from_millis(125),
-            ),
-        );
-        player_animations.insert(
-            PlayerState::Hurt,
-            Animation::new(
-                images.get_from_pattern("game/hurt_*.png"),
-                Duration::from_millis(125),
-            ),
-        );
-        player_animations.insert(
-            PlayerState::Dead,
-            Animation::new(
-                images.get_from_pattern("game/death_*.png"),
-                Duration::from_millis(125),
-            ),
-        );
-
-        let mut source =
-            Source::from_data(ctx, sounds["/audio/game/audio_loop.ogg"].clone())?;
-        source.set_repeat(true);
-        source.play_detached()?;
-
-        Ok(MainState {
-            maze,
-            info: Text::new("10"),
-            start: Instant::now(),
-            found: 0,
-            player: Player::new(player_animations, Source::from_data(ctx, sounds["/audio/game/running.ogg"].clone())?),
-            images,
-            sounds,
-            hidden: false,
-            dead_since: None,
-            death_sound: Source::from_data(ctx, sounds["/audio/game/death.ogg"].clone()).unwrap()
-        })
-    }
-}
-
-impl EventHandler for MainState<'_> {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        if let Some(since) = self.dead_since {
-            if (Instant::now() - since).as_millis() >= 800 {
-                println!("Perdu UwU");
-                std::process::exit(0);
-            }
-        }
-
-        let diff = 10i32 - (Instant::now() - self.start).as_secs() as i32;
-        if diff > 0 {
-            self.info = Text::new(format!("{:02}", diff));
-        } else {
-            self.info = Text::new(format!("{}/3", self.found));
-            self.hidden = true;
-        }
-
-        if !self.hidden {
-            return Ok(());
-        }
-
-        let (fx, fy) = self.player.pos;
-        let (x, y) = (fx as usize, fy as usize);
-
-        for cx in 0..20 {
-            for cy in 0..20 {
-                if let Some(reward) = self.maze.get_mut_reward([cx, cy].into()) {
-                    if self.hidden {
-                        reward.texture = self.images["/game/pan_empty.png"].clone();
-                    }
-
-                    if cx != x || cy != y || reward.found {
-                        continue;
-                    }
-
-                    reward.found = true;
-
-                    if reward.malus {
-                        self.death_sound.set_volume(2.0);
-                        self.death_sound.play_detached()?;
-
-                        self.player.set_state(PlayerState::Dead);
-                        self.dead_since = Some(Instant::now());
-                    } else {
-                        self.found += 1;
-                    }
-                }
-            }
-        }
-
-        if self.found == 3 && x == 20 && y == 19 {
-            println!("Gagné OwO");
-            std::process::exit(0);
-        }
-
-        if !self.player.is_dead() {
-            if keyboard::is_key_pressed(ctx, KeyCode::Up) {
-                if y != 0 && !self.maze.get([x, y - 1].into()).is_wall() {
-                    self.player.translate((0.0, -1.0));
-                }
-            } else if keyboard::is_key_pressed(ctx, KeyCode::Down) {
-                if y != 20 && !self.maze.get([x, y + 1].into()).is_wall() {
-                    self.player.translate((0.0, 1.0));
-                }
-            } else if keyboard::is_key_pressed(ctx, KeyCode::Left) {
-                self.player.set_flipped(true);
-                if x != 0 && !self.maze.get([x - 1, y].into()).is_wall() {
-                    self.player.translate((-1.0, 0.0));
-                }
-            } else if keyboard::is_key_pressed(ctx, KeyCode::Right) {
-                self.player.set_flipped(false);
-                if x != 20 && !self.maze.get([x + 1, y].into()).is_wall() {
-                    self.player.translate((1.0, 0.0));
-                }
-            }
-        }
-        self.player.update(ctx);
-
-        Ok(())
-    }
-
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
-
-        graphics::draw(
-            ctx,
-            &self.maze,
-            DrawParam::new().dest([0.0, 0.0]).scale([1., 1.]),
-        )?;
-        graphics::draw(ctx, &self.player, (na::Point2::new(0.0, 0.0),))?;
-
-        graphics::draw(
-            ctx,
-            &self.images["/ui/panel_brown.png"],
-            DrawParam::new()
-                .dest(na::Point2::new(690.0, 40.0))
-                .scale(na::Vector2::new(0.9, 0.5)),
-        )?;
-        graphics::draw(
-            ctx,
-            &self.images["/ui/panelInset_beige.png"],
-            DrawParam::new()
-                .dest(na::Point2::new(697.5, 47.5))
-                .scale(na::Vector2::new(0.8, 0.38)),
-        )?;
-        graphics::draw(
-            ctx,
-            &self.info,
-            DrawParam::new()
-                .dest(na::Point2::new(715.0, 50.0))
-                .scale(na::Vector2::new(2.0, 2.0)),
-        )?;
-
-        if self.hidden {
-            let (x, y) = self.player.pos;
-
-            let black = Color::from_rgb(0, 0, 0);
-            let mut mesh = MeshBuilder::new();
-
-            mesh.rectangle(
-                DrawMode::Fill(FillOptions::DEFAULT),
-                Rect {
-                    x: 0.0,
-                    y: 0.0,
-                    w: x * 32.0 - 15.0,
-                    h: 675.0,
-                },
-                black.clone(),
-            );
-            mesh.rectangle(
-                DrawMode::Fill(FillOptions::DEFAULT),
-                Rect {
-                    x: x * 32.0 - 15.0,
-                    y: 0.0,
-                    w: 62.5,
-                    h: y * 32.0 - 15.0,
-                },
-                black.clone(),
-            );
-            mesh.rectangle(
-                DrawMode::Fill(FillOptions::DEFAULT),
-                Rect {
-                    x: x * 32.0 - 15.0,
-                    y: y * 32.0 - 10.0 + 50.0,
-                    w: 62.5,
-                    h: 675.0 - y * 32.0 - 15.0 + 55.0,
-                },
-                black.clone(),
-            );
-            mesh.rectangle(
-                DrawMode::Fill(FillOptions::DEFAULT),
-                Rect {
-                    x: x * 32.0 - 15.0 + 62.5,
-                    y: 0.0,
-                    w: 580.0 - x * 32.0 - 15.0 + 62.5,
-                    h: 675.0,
-                },
-                black.clone(),
-            );
-
-            let mesh = &mesh.build(ctx)?;
-            graphics::draw(ctx, mesh, (na::Point2::new(0.0, 0.0),))?;
-        }
-
-        graphics::present(ctx)?;
+fn main() -> GameResult {
+    let options = Options::from_args(env::args().skip(1))?;
+    let game = scenes::new_game(&options, options.seed)?;
 
-        Ok(())
+    if let Some(path) = &options.export {
+        fs::write(path, game.maze().export(Format::from_path(path)?))?;
+        return Ok(());
     }
-}
 
-fn main() -> GameResult {
     let resource_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = path::PathBuf::from(manifest_dir);
         path.push("assets");
@@ -275,6 +36,25 @@ fn main() -> GameResult {
     };
     let path = resource_dir.clone();
 
+    let campaign = Campaign::load(&path.join("levels/campaign.json"))?;
+    let replay = match &options.replay {
+        Some(path) => Some(Replay::from_json(&fs::read_to_string(path)?)?),
+        None => None,
+    };
+
+    // smaller mazes are centered in the view
+    let (mut w, mut h) = game.maze().dim();
+    for (other_w, other_h) in replay
+        .iter()
+        .filter_map(|replay| replay.game().ok())
+        .map(|game| game.maze().dim())
+        .chain(std::iter::once(campaign.max_size()))
+    {
+        w = w.max(other_w);
+        h = h.max(other_h);
+    }
+    let layout = Layout::new((w, h), options.zoom);
+
     let (ctx, event_loop) = &mut ContextBuilder::new("pate2crabe", "team_pate2crabe")
         .window_setup(WindowSetup {
             title: "pate2crabe".to_owned(),
@@ -284,8 +64,8 @@ fn main() -> GameResult {
             srgb: true,
         })
         .window_mode(WindowMode {
-            width: 800.0,
-            height: 675.0,
+            width: layout.window.0,
+            height: layout.window.1,
             maximized: false,
             fullscreen_type: FullscreenType::Windowed,
             borderless: false,
@@ -299,6 +79,28 @@ fn main() -> GameResult {
         .build()?;
     let images = &Assets::load(&path, &["png"], |path| Image::new(ctx, path))?;
     let sounds = &Assets::load(&path, &["ogg", "wav"], |path| SoundData::new(ctx, path))?;
-    let state = &mut MainState::new(ctx, images, sounds)?;
+    let progress = Progress::load(ctx);
+    let leaderboard = Leaderboard::load(ctx);
+    let mut shared = Shared::new(
+        images,
+        sounds,
+        options,
+        layout,
+        campaign,
+        progress,
+        leaderboard,
+    );
+    shared.ghosts = Ghosts::load(ctx);
+    shared.controls = Controls::load(ctx);
+    // the campaign may have lost levels since
+    shared.save = SaveGame::load(ctx).filter(|save| match save.mode {
+        Mode::Campaign(level) => level < shared.campaign.levels.len(),
+        _ => true,
+    });
+    let first: Box<dyn Scene> = match replay {
+        Some(replay) => Box::new(Replaying::new(&shared, replay)?),
+        None => Box::new(Title::new(&shared, Some(game))),
+    };
+    let state = &mut SceneStack::new(first, shared);
     event::run(ctx, event_loop, state)
 }
@@ -1,25 +1,79 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use crate::generator::MazeGenerator;
 use crate::rewards::Reward;
-use ggez::graphics::{BlendMode, DrawParam, Drawable, Image, Rect};
-use ggez::nalgebra as na;
-use ggez::{Context, GameResult};
-use na::{Point2, Vector2};
+use nalgebra::{Point2, Vector2};
 use rand::prelude::*;
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
 
-use crate::assets::Assets;
 use crate::tile::Tile;
 
 pub type CellIndex = Point2<usize>;
 
-#[derive(Debug, Clone)]
+/// RNG used for generation, its output only depends on the seed, whatever the platform
+pub type MazeRng = Pcg32;
+
+/// Number of rewards to place by default, which is also the number to collect
+pub const REWARDS: usize = 3;
+pub const MALUSES: usize = 3;
+
+/// How many rewards and maluses a generated maze holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Population {
+    pub rewards: usize,
+    pub maluses: usize,
+}
+
+impl Default for Population {
+    fn default() -> Self {
+        Population {
+            rewards: REWARDS,
+            maluses: MALUSES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Maze {
     /// (width, height)
     dim: (usize, usize),
     tiles: Vec<Tile>,
-    grass_asset: Image,
+    /// Kept in row-major order, so that equal mazes compare equal whatever the way they were built
     rewards: Vec<Reward>,
+    seed: Option<u64>,
+    start: CellIndex,
+    /// Opening in the outer wall
+    exit: CellIndex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MazeError {
+    /// The maze has too few free cells to hold every reward
+    NotEnoughRoom,
+    /// This reward or exit cannot be reached without stepping on a malus
+    Unreachable(CellIndex),
+    /// More than one of the start, the exit and the rewards are on this cell
+    Overlapping(CellIndex),
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeError::NotEnoughRoom => write!(f, "the maze is too small for its rewards"),
+            MazeError::Unreachable(pos) => write!(f, "({}, {}) is unreachable", pos.x, pos.y),
+            MazeError::Overlapping(pos) => {
+                write!(f, "({}, {}) holds more than one thing", pos.x, pos.y)
+            }
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+impl Error for MazeError {}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum Direction {
     North,
     East,
@@ -37,6 +91,24 @@ impl Direction {
         }
         .into()
     }
+
+    /// The direction on the right when facing this one
+    pub fn right(self) -> Direction {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    pub fn left(self) -> Direction {
+        self.right().opposite()
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.right().right()
+    }
 }
 
 pub const DIRECTIONS: [Direction; 4] = [
@@ -47,195 +119,316 @@ pub const DIRECTIONS: [Direction; 4] = [
 ];
 
 impl Maze {
-    pub fn new((w, h): (usize, usize), images: &Assets<Image>) -> Self {
+    pub fn new((w, h): (usize, usize)) -> Self {
         Maze {
             dim: (w, h),
-            tiles: vec![Tile::Wall(None); w * h],
-            grass_asset: images["/game/grass.png"].clone(),
+            tiles: vec![Tile::Wall; w * h],
             rewards: vec![],
+            seed: None,
+            start: [1, 1].into(),
+            exit: [w - 1, h - 2].into(),
         }
     }
 
-    pub fn generate<R: Rng>(&mut self, rng: &mut R, images: &Assets<Image>) {
-        loop {
-            // generate 3 rewards and 3 maluses
-            for i in 0..6 {
-                loop {
-                    let pos: CellIndex = [
-                        rng.gen_range(0, self.dim.0 / 2) * 2 + 1,
-                        rng.gen_range(0, self.dim.1 / 2) * 2 + 1,
-                    ]
-                    .into();
-
-                    if pos != CellIndex::from([1, 1]) && self.get_reward(pos).is_none() {
-                        self.rewards.push(Reward::new(images, pos, i > 2));
-                        break;
-                    }
-                }
-            }
+    /// Generates a maze that is always the same for a given seed, size and generator
+    pub fn from_seed(
+        dim: (usize, usize),
+        seed: u64,
+        generator: &dyn MazeGenerator,
+    ) -> Result<Self, MazeError> {
+        Maze::from_seed_with(dim, seed, generator, Population::default())
+    }
 
-            self.set([self.dim.0 - 1, self.dim.1 - 2].into(), Tile::Ground);
+    /// Same as `from_seed`, with a given number of rewards and maluses
+    pub fn from_seed_with(
+        dim: (usize, usize),
+        seed: u64,
+        generator: &dyn MazeGenerator,
+        population: Population,
+    ) -> Result<Self, MazeError> {
+        let mut maze = Maze::new(dim);
+        maze.generate(generator, population, &mut MazeRng::seed_from_u64(seed))?;
+        maze.seed = Some(seed);
+        Ok(maze)
+    }
 
-            // start at (1, 1)
-            self.backtrack_gen([1, 1].into(), rng);
-            self.set_textures(images);
+    /// The seed the maze was generated from, if any
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
 
-            if self.is_correct() {
-                break;
-            }
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// (width, height)
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
 
-            self.tiles = vec![Tile::Wall(None); self.dim.0 * self.dim.1];
+    /// Carves the maze, then places the rewards and maluses. Tiny mazes may lack room for every
+    /// malus, so a few layouts are tried before giving up, which keeps generation bounded.
+    pub fn generate(
+        &mut self,
+        generator: &dyn MazeGenerator,
+        population: Population,
+        rng: &mut MazeRng,
+    ) -> Result<(), MazeError> {
+        const MAX_ATTEMPTS: usize = 16;
+
+        let mut result = Ok(());
+
+        for _ in 0..MAX_ATTEMPTS {
+            self.tiles = vec![Tile::Wall; self.dim.0 * self.dim.1];
             self.rewards.clear();
+
+            generator.carve(self, rng);
+            self.set(self.exit, Tile::Ground);
+
+            result = self
+                .place_rewards(population, rng)
+                .and_then(|_| self.validate());
+            if result.is_ok() {
+                break;
+            }
         }
+
+        self.sort_rewards();
+        result
     }
 
-    fn is_correct(&self) -> bool {
+    /// Puts maluses on dead ends far from the start, where they cannot block any path, and
+    /// rewards on the remaining far dead ends. Small mazes may lack dead ends, the other cells
+    /// are then used as long as the maze stays valid.
+    fn place_rewards(
+        &mut self,
+        Population { rewards, maluses }: Population,
+        rng: &mut MazeRng,
+    ) -> Result<(), MazeError> {
+        let distances = self.distances(self.start, |_| true);
+
+        let mut dead_ends = vec![];
+        let mut others = vec![];
+
         for y in (1..self.dim.1).step_by(2) {
             for x in (1..self.dim.0).step_by(2) {
                 let pos = CellIndex::from([x, y]);
+                let openings: Vec<_> = DIRECTIONS
+                    .iter()
+                    .filter_map(|&dir| self.neighbour(pos, dir))
+                    .filter(|&n| !self.get(n).is_wall())
+                    .collect();
 
-                if self.get(pos).is_wall() {
-                    return false;
+                if pos == self.start || openings.contains(&self.exit) || self.get(pos).is_wall() {
+                    continue;
+                }
+
+                if let Some(distance) = distances[self.index(pos)] {
+                    // rewards are preferably not entered from above
+                    let from_above = self.get_tile_rel(pos, Direction::North) == Some(Tile::Ground);
+
+                    if openings.len() == 1 && !from_above {
+                        dead_ends.push((distance, pos));
+                    } else {
+                        others.push((openings.len() == 1, pos));
+                    }
                 }
             }
         }
-        true
-    }
 
-    pub fn get_reward(&self, pos: CellIndex) -> Option<&Reward> {
-        self.rewards.iter().find(|r| r.pos() == &pos)
-    }
+        if dead_ends.len() + others.len() < rewards + maluses {
+            return Err(MazeError::NotEnoughRoom);
+        }
 
-    pub fn get_mut_reward(&mut self, pos: CellIndex) -> Option<&mut Reward> {
-        self.rewards.iter_mut().find(|r| r.pos() == &pos)
-    }
+        // farthest dead ends first, ties broken by position to stay deterministic
+        dead_ends.sort_by_key(|&(distance, pos)| (std::cmp::Reverse(distance), pos.y, pos.x));
+        let far = (dead_ends.len() / 2)
+            .max(rewards + maluses)
+            .min(dead_ends.len());
+
+        let mut candidates: Vec<_> = dead_ends.iter().map(|&(_, pos)| (true, pos)).collect();
+        candidates[..far].shuffle(rng);
+        candidates[far..].shuffle(rng);
+        others.shuffle(rng);
+        // remaining dead ends, entered from above, come before corridors
+        others.sort_by_key(|&(dead_end, _)| !dead_end);
+        candidates.extend(others);
+
+        let placed: Vec<_> = candidates
+            .iter()
+            .filter(|&&(dead_end, _)| dead_end)
+            .map(|&(_, pos)| pos)
+            .take(maluses)
+            .collect();
+        for &pos in &placed {
+            self.rewards.push(Reward::new(pos, true));
+        }
+
+        let mut free = candidates
+            .into_iter()
+            .map(|(_, pos)| pos)
+            .filter(|pos| !placed.contains(pos));
 
-    fn backtrack_gen<R: Rng>(&mut self, curr: CellIndex, rng: &mut R) {
-        let curr_as_vec: Vector2<isize> = [curr.x as isize, curr.y as isize].into();
+        for pos in free.by_ref().take(rewards) {
+            self.rewards.push(Reward::new(pos, false));
+        }
+
+        // not enough dead ends left, only keep maluses that do not cut a path
+        let mut missing = maluses - placed.len();
+        for pos in free {
+            if missing == 0 {
+                break;
+            }
 
-        // mark as visited
-        self.set(curr, Tile::Ground);
+            self.rewards.push(Reward::new(pos, true));
+            if self.validate().is_ok() {
+                missing -= 1;
+            } else {
+                self.rewards.pop();
+            }
+        }
 
-        if curr == [self.dim.0 - 2, self.dim.1 - 2].into() {
-            // end at (self.dim.0 - 2, self.dim.1 - 2)
-            return;
+        if missing > 0 {
+            return Err(MazeError::NotEnoughRoom);
         }
+        Ok(())
+    }
 
-        if self.get_reward(curr).is_some() {
-            // end at rewards
-            return;
+    /// Whether `pos` can be walked through unharmed, a malus stays harmful once found
+    pub fn is_safe(&self, pos: CellIndex) -> bool {
+        !matches!(self.get_reward(pos), Some(r) if r.malus)
+    }
+
+    /// Checks that the start, the exit and the rewards are on distinct cells, and that every
+    /// reward and the exit can be reached from the start without stepping on a malus
+    pub fn validate(&self) -> Result<(), MazeError> {
+        let mut cells: Vec<_> = self
+            .rewards
+            .iter()
+            .map(|r| *r.pos())
+            .chain(vec![self.start, self.exit])
+            .collect();
+        cells.sort_by_key(|pos| (pos.y, pos.x));
+        if let Some(pair) = cells.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(MazeError::Overlapping(pair[0]));
         }
 
-        let mut directions = DIRECTIONS;
-        directions.shuffle(rng);
-
-        // find unvisited neighbours
-        for dir in &directions {
-            let index_ = curr_as_vec + dir.as_relative() * 2;
-            let index: CellIndex = [index_.x as usize, index_.y as usize].into();
-
-            // between is the cell between the current one and the neighbour
-            let between_ = curr_as_vec + dir.as_relative();
-            let between: CellIndex = [between_.x as usize, between_.y as usize].into();
-
-            // if neighbour is not visited
-            if (1..self.dim.0 - 1).contains(&index.x)
-                && (1..self.dim.1 - 1).contains(&index.y)
-                && self.get(index).is_wall()
-                && (self.get_reward(index).is_none() || dir != &Direction::South)
-            // do not go down if reward there
-            {
-                self.set(between, Tile::Ground);
-                self.backtrack_gen(index, rng);
+        let distances = self.distances(self.start, |pos| self.is_safe(pos));
+
+        self.rewards
+            .iter()
+            .filter(|r| !r.malus)
+            .map(|r| *r.pos())
+            .chain(std::iter::once(self.exit))
+            .try_for_each(|pos| match distances[self.index(pos)] {
+                Some(_) => Ok(()),
+                None => Err(MazeError::Unreachable(pos)),
+            })
+    }
+
+    /// Breadth-first search from `from` over the ground tiles accepted by `passable`, returns the
+    /// distance to every tile in row-major order, `None` for unreachable ones
+    pub fn distances<F: Fn(CellIndex) -> bool>(
+        &self,
+        from: CellIndex,
+        passable: F,
+    ) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.tiles.len()];
+        let mut queue = VecDeque::new();
+
+        distances[self.index(from)] = Some(0);
+        queue.push_back(from);
+
+        while let Some(pos) = queue.pop_front() {
+            let distance = distances[self.index(pos)].unwrap();
+
+            for &dir in &DIRECTIONS {
+                if let Some(next) = self.neighbour(pos, dir) {
+                    if distances[self.index(next)].is_none()
+                        && !self.get(next).is_wall()
+                        && passable(next)
+                    {
+                        distances[self.index(next)] = Some(distance + 1);
+                        queue.push_back(next);
+                    }
+                }
             }
         }
+
+        distances
     }
 
-    pub fn get(&self, pos: CellIndex) -> &Tile {
-        assert!(self.is_in_range(pos));
-        &self.tiles[pos.y * self.dim.0 + pos.x]
+    pub fn start(&self) -> CellIndex {
+        self.start
     }
 
-    pub fn set(&mut self, pos: CellIndex, tile: Tile) {
-        assert!(self.is_in_range(pos));
-        self.tiles[pos.y * self.dim.0 + pos.x] = tile;
+    pub fn exit(&self) -> CellIndex {
+        self.exit
     }
 
-    pub fn is_in_range(&self, pos: CellIndex) -> bool {
-        (0..self.dim.0).contains(&pos.x) && (0..self.dim.1).contains(&pos.y)
+    pub fn set_start(&mut self, pos: CellIndex) {
+        self.start = pos;
     }
 
-    fn set_textures(&mut self, images: &Assets<Image>) {
-        for y in 0..self.dim.1 {
-            for x in 0..self.dim.0 {
-                let index = CellIndex::from([x, y]);
+    pub fn set_exit(&mut self, pos: CellIndex) {
+        self.exit = pos;
+    }
 
-                if !self.get(index).is_wall() {
-                    continue;
-                }
+    pub fn add_reward(&mut self, reward: Reward) {
+        self.rewards.push(reward);
+        self.sort_rewards();
+    }
 
-                let texture = match (
-                    matches!(
-                        self.get_tile_rel(index, Direction::South),
-                        Some(Tile::Wall(_))
-                    ),
-                    matches!(
-                        self.get_tile_rel(index, Direction::East),
-                        Some(Tile::Wall(_))
-                    ),
-                    matches!(
-                        self.get_tile_rel(index, Direction::North),
-                        Some(Tile::Wall(_))
-                    ),
-                    matches!(
-                        self.get_tile_rel(index, Direction::West),
-                        Some(Tile::Wall(_))
-                    ),
-                ) {
-                    (true, true, false, false) => {
-                        Some(images["/game/wall_corn_top_lft.png"].clone())
-                    }
-                    (true, false, false, true) => {
-                        Some(images["/game/wall_corn_top_rgt.png"].clone())
-                    }
-                    (false, true, true, false) => {
-                        Some(images["/game/wall_corn_bot_lft.png"].clone())
-                    }
-                    (false, false, true, true) => {
-                        Some(images["/game/wall_corn_bot_rgt.png"].clone())
-                    }
+    fn sort_rewards(&mut self) {
+        self.rewards.sort_by_key(|r| (r.pos().y, r.pos().x));
+    }
 
-                    (true, true, true, true) => Some(images["/game/wall_crss_all.png"].clone()),
-                    (true, true, false, true) => {
-                        Some(images["/game/wall_crss_hori_top.png"].clone())
-                    }
-                    (false, true, true, true) => {
-                        Some(images["/game/wall_crss_hori_bot.png"].clone())
-                    }
-                    (true, true, true, false) => {
-                        Some(images["/game/wall_crss_vert_lft.png"].clone())
-                    }
-                    (true, false, true, true) => {
-                        Some(images["/game/wall_crss_vert_rgt.png"].clone())
-                    }
+    pub fn rewards(&self) -> &[Reward] {
+        &self.rewards
+    }
+
+    pub fn get_reward(&self, pos: CellIndex) -> Option<&Reward> {
+        self.rewards.iter().find(|r| r.pos() == &pos)
+    }
 
-                    (false, false, false, true) => Some(images["/game/wall_hori_rgt.png"].clone()),
-                    (false, true, false, true) => Some(images["/game/wall_hori_mid.png"].clone()),
-                    (false, true, false, false) => Some(images["/game/wall_hori_lft.png"].clone()),
+    pub fn get_mut_reward(&mut self, pos: CellIndex) -> Option<&mut Reward> {
+        self.rewards.iter_mut().find(|r| r.pos() == &pos)
+    }
 
-                    (true, false, false, false) => Some(images["/game/wall_vert_top.png"].clone()),
-                    (true, false, true, false) => Some(images["/game/wall_vert_mid.png"].clone()),
-                    (false, false, true, false) => Some(images["/game/wall_vert_bot.png"].clone()),
+    pub fn get(&self, pos: CellIndex) -> Tile {
+        self.tiles[self.index(pos)]
+    }
 
-                    (false, false, false, false) => None,
-                };
+    pub fn set(&mut self, pos: CellIndex, tile: Tile) {
+        let index = self.index(pos);
+        self.tiles[index] = tile;
+    }
 
-                self.set(index, Tile::Wall(texture));
-            }
-        }
+    /// Index of a tile in row-major order
+    pub fn index(&self, pos: CellIndex) -> usize {
+        assert!(self.is_in_range(pos));
+        pos.y * self.dim.0 + pos.x
+    }
+
+    pub fn is_in_range(&self, pos: CellIndex) -> bool {
+        (0..self.dim.0).contains(&pos.x) && (0..self.dim.1).contains(&pos.y)
+    }
+
+    /// Whether the tiles fill the dimensions and every position is in range, which a
+    /// deserialized maze may not ensure
+    pub fn is_consistent(&self) -> bool {
+        self.tiles.len() == self.dim.0 * self.dim.1
+            && self.is_in_range(self.start)
+            && self.is_in_range(self.exit)
+            && self.rewards.iter().all(|r| self.is_in_range(*r.pos()))
     }
 
-    fn get_tile_rel(&self, pos: CellIndex, dir: Direction) -> Option<&Tile> {
+    pub fn get_tile_rel(&self, pos: CellIndex, dir: Direction) -> Option<Tile> {
+        self.neighbour(pos, dir).map(|pos2| self.get(pos2))
+    }
+
+    /// Returns the cell next to `pos` in the given direction, if it is inside the maze
+    pub fn neighbour(&self, pos: CellIndex, dir: Direction) -> Option<CellIndex> {
         let rel = dir.as_relative();
         let pos2 = CellIndex::from([
             (pos.x as isize + rel.x) as usize,
@@ -244,63 +437,7 @@ impl Maze {
 
         match self.is_in_range(pos2) {
             false => None,
-            true => Some(self.get(pos2)),
-        }
-    }
-}
-
-impl Drawable for Maze {
-    fn draw(&self, ctx: &mut Context, param: DrawParam) -> GameResult<()> {
-        for x in 0..self.dim.0 {
-            for y in 0..self.dim.1 {
-                let param2 = param.clone().dest(Point2::new(
-                    param.dest.x + x as f32 * 32. * param.scale.x,
-                    param.dest.y + y as f32 * 32. * param.scale.y,
-                ));
-
-                // debug
-                // graphics::Mesh::new_rectangle(
-                //     ctx,
-                //     graphics::DrawMode::stroke(1.0),
-                //     Rect::new(0., 0., 32., 32.),
-                //     graphics::WHITE,
-                // )?
-                // .draw(ctx, param2)?;
-
-                self.grass_asset.draw(ctx, param2)?;
-                self.get([x, y].into()).draw(ctx, param2)?;
-            }
-        }
-        for r in &self.rewards {
-            if r.found {
-                continue;
-            }
-
-            r.draw(
-                ctx,
-                param.clone().dest([
-                    param.dest.x + r.pos().x as f32 * 32. * param.scale.x,
-                    param.dest.y + (r.pos().y as f32 * 32. - 24.) * param.scale.y,
-                ]),
-            )?;
+            true => Some(pos2),
         }
-        Ok(())
-    }
-
-    fn dimensions(&self, _ctx: &mut Context) -> Option<Rect> {
-        Option::from(Rect::new(
-            0.,
-            0.,
-            self.dim.0 as f32 * 32.,
-            self.dim.1 as f32 * 32.,
-        ))
-    }
-
-    fn set_blend_mode(&mut self, _mode: Option<BlendMode>) {
-        unimplemented!()
-    }
-
-    fn blend_mode(&self) -> Option<BlendMode> {
-        unimplemented!()
     }
 }
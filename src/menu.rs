@@ -0,0 +1,123 @@
+use std::ops::Range;
+
+use ggez::graphics::{self, DrawParam, Image, Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::nalgebra::{Point2, Vector2};
+use ggez::{Context, GameResult};
+
+use crate::assets::Assets;
+
+/// Size of a `buttonLong` image
+const BUTTON_SIZE: (f32, f32) = (190., 49.);
+/// Vertical distance between two buttons
+const BUTTON_SPACING: f32 = 56.;
+
+/// A vertical list of buttons, browsed with the arrow keys and chosen with Enter or Space
+pub struct Menu<A> {
+    items: Vec<(Text, A)>,
+    selected: usize,
+    chosen: Option<A>,
+}
+
+impl<A: Copy> Menu<A> {
+    pub fn new<S: AsRef<str>>(items: &[(S, A)]) -> Menu<A> {
+        Menu {
+            items: items
+                .iter()
+                .map(|(label, action)| {
+                    let text =
+                        Text::new(TextFragment::new(label.as_ref()).scale(Scale::uniform(22.)));
+                    (text, *action)
+                })
+                .collect(),
+            selected: 0,
+            chosen: None,
+        }
+    }
+
+    /// Height of the menu, in pixels, when at most `max_height` is available
+    pub fn height(&self, max_height: f32) -> f32 {
+        BUTTON_SPACING * self.visible(max_height).len() as f32
+    }
+
+    /// Items that fit in `max_height`, scrolled so that the selected one is shown
+    fn visible(&self, max_height: f32) -> Range<usize> {
+        let count = ((max_height / BUTTON_SPACING) as usize).clamp(1, self.items.len());
+        let first = (self.selected + 1).saturating_sub(count);
+
+        first..first + count
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len() - 1);
+    }
+
+    pub fn key_down(&mut self, keycode: KeyCode) {
+        let count = self.items.len();
+
+        match keycode {
+            KeyCode::Up => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down => self.selected = (self.selected + 1) % count,
+            KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space => {
+                self.chosen = Some(self.items[self.selected].1)
+            }
+            _ => {}
+        }
+    }
+
+    /// The action chosen since the last call, if any
+    pub fn take_chosen(&mut self) -> Option<A> {
+        self.chosen.take()
+    }
+
+    /// Draws the buttons below `top`, centered on `center_x`, scrolling past `max_height`
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        images: &Assets<Image>,
+        center_x: f32,
+        top: f32,
+        max_height: f32,
+    ) -> GameResult {
+        let visible = self.visible(max_height);
+
+        for (row, i) in visible.enumerate() {
+            let (text, _) = &self.items[i];
+            let selected = i == self.selected;
+            let mut dest = Point2::new(
+                center_x - BUTTON_SIZE.0 / 2.,
+                top + row as f32 * BUTTON_SPACING,
+            );
+
+            if selected {
+                graphics::draw(
+                    ctx,
+                    &images["/ui/arrowBrown_right.png"],
+                    DrawParam::new().dest(dest + Vector2::new(-28., 14.)),
+                )?;
+                // pressed buttons are shorter, they sink instead
+                dest.y += 4.;
+            }
+            let button = if selected {
+                "/ui/buttonLong_brown_pressed.png"
+            } else {
+                "/ui/buttonLong_brown.png"
+            };
+            graphics::draw(ctx, &images[button], DrawParam::new().dest(dest))?;
+
+            let (w, h) = text.dimensions(ctx);
+            graphics::draw(
+                ctx,
+                text,
+                DrawParam::new().dest(
+                    dest + Vector2::new(
+                        (BUTTON_SIZE.0 - w as f32) / 2.,
+                        (BUTTON_SIZE.1 - 4. - h as f32) / 2.,
+                    ),
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
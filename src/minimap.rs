@@ -0,0 +1,102 @@
+//! A small map of the maze in the side panel, drawn from what the player explored under the fog
+
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+use crate::game::Game;
+use crate::maze::CellIndex;
+
+const WALL_COLOR: Color = Color::new(0.35, 0.25, 0.15, 1.);
+const GROUND_COLOR: Color = Color::new(0.45, 0.7, 0.3, 1.);
+const REWARD_COLOR: Color = Color::new(0.9, 0.15, 0.1, 1.);
+const MALUS_COLOR: Color = Color::new(0.5, 0.2, 0.6, 1.);
+const EXIT_COLOR: Color = Color::new(0.2, 0.5, 1., 1.);
+const PLAYER_COLOR: Color = Color::new(1., 1., 1., 1.);
+
+/// Shows the cells explored so far, or the whole maze once switched to the full map
+#[derive(Debug, Clone, Default)]
+pub struct Minimap {
+    full: bool,
+}
+
+impl Minimap {
+    /// Switches between the explored cells and the full map
+    pub fn toggle(&mut self) {
+        self.full = !self.full;
+    }
+
+    /// Draws `game` as big as it fits in `area`, keeping its proportions
+    pub fn draw(&self, ctx: &mut Context, game: &Game, area: Rect) -> GameResult {
+        let maze = game.maze();
+        let (w, h) = maze.dim();
+        let size = (area.w / w as f32).min(area.h / h as f32);
+        let origin = (
+            area.x + (area.w - w as f32 * size) / 2.,
+            area.y + (area.h - h as f32 * size) / 2.,
+        );
+        let cell = |(x, y): (f32, f32), scale: f32| {
+            let margin = size * (1. - scale) / 2.;
+            Rect::new(
+                origin.0 + x * size + margin,
+                origin.1 + y * size + margin,
+                size * scale,
+                size * scale,
+            )
+        };
+        // everything is in sight until the fog falls
+        let known = |pos| self.full || !game.is_hidden() || game.vision().is_explored(maze, pos);
+
+        let mut mesh = MeshBuilder::new();
+        let mut empty = true;
+        for y in 0..h {
+            for x in 0..w {
+                let pos = CellIndex::from([x, y]);
+                if !known(pos) {
+                    continue;
+                }
+                let color = if maze.get(pos).is_wall() {
+                    WALL_COLOR
+                } else {
+                    GROUND_COLOR
+                };
+                mesh.rectangle(DrawMode::fill(), cell((x as f32, y as f32), 1.), color);
+                empty = false;
+            }
+        }
+        if empty {
+            return Ok(());
+        }
+
+        for reward in maze.rewards().iter().filter(|r| r.found) {
+            let color = if reward.malus {
+                MALUS_COLOR
+            } else {
+                REWARD_COLOR
+            };
+            let pos = reward.pos();
+            mesh.rectangle(
+                DrawMode::fill(),
+                cell((pos.x as f32, pos.y as f32), 0.8),
+                color,
+            );
+        }
+        let exit = maze.exit();
+        if known(exit) {
+            mesh.rectangle(
+                DrawMode::fill(),
+                cell((exit.x as f32, exit.y as f32), 1.),
+                EXIT_COLOR,
+            );
+        }
+        // the player stays visible when the cells are tiny
+        let scale = (3. / size).max(1.);
+        mesh.rectangle(
+            DrawMode::fill(),
+            cell(game.player().pos(), scale),
+            PLAYER_COLOR,
+        );
+
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::new())
+    }
+}
@@ -0,0 +1,119 @@
+use std::env;
+use std::path::PathBuf;
+
+use ggez::{GameError, GameResult};
+use rand::Rng;
+
+use crate::camera::{MAX_ZOOM, MIN_ZOOM};
+use crate::generator::Algorithm;
+
+/// Startup options, read from the command line
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Seed of the maze, a random one is picked when not given
+    pub seed: u64,
+    pub algorithm: Algorithm,
+    /// (width, height) of the maze, in tiles
+    pub size: (usize, usize),
+    /// Scale of the tiles, mazes that do not fit in the window scroll
+    pub zoom: f32,
+    /// Maze to play instead of a generated one, a `.tmx`, `.txt` or `.json` file
+    pub level: Option<PathBuf>,
+    /// File to write the maze to instead of playing, its extension gives the format
+    pub export: Option<PathBuf>,
+    /// Recorded game to watch, the last game played is kept in `replay.json` in the user
+    /// directory
+    pub replay: Option<PathBuf>,
+    /// Name written in the leaderboard, the user name when not given
+    pub name: String,
+    /// Number of enemies, overrides the one of the level when given
+    pub enemies: Option<u8>,
+}
+
+impl Options {
+    /// Parses options such as `--seed 1234 --algorithm prim --size 31x21 --zoom 1.5 --enemies 2` or
+    /// `--level levels/sample.tmx --export maze.json --replay replay.json --name Ferris`, the
+    /// program name must not be included
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> GameResult<Options> {
+        let mut seed = None;
+        let mut algorithm = Algorithm::default();
+        let mut size = (21, 21);
+        let mut zoom = 1.;
+        let mut level = None;
+        let mut export = None;
+        let mut replay = None;
+        let mut name = None;
+        let mut enemies = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => seed = Some(parse_value(&arg, args.next())?),
+                "--algorithm" => algorithm = parse_value(&arg, args.next())?,
+                "--zoom" => zoom = parse_zoom(parse_value(&arg, args.next())?)?,
+                "--level" => level = Some(parse_value(&arg, args.next())?),
+                "--export" => export = Some(parse_value(&arg, args.next())?),
+                "--replay" => replay = Some(parse_value(&arg, args.next())?),
+                "--name" => name = Some(parse_value(&arg, args.next())?),
+                "--enemies" => enemies = Some(parse_value(&arg, args.next())?),
+                "--size" => size = parse_size(&parse_value::<String>(&arg, args.next())?)?,
+                _ => return Err(GameError::ConfigError(format!("unknown option {}", arg))),
+            }
+        }
+
+        Ok(Options {
+            // short enough to be read on the side panel
+            seed: seed.unwrap_or_else(|| rand::thread_rng().gen::<u32>() as u64),
+            algorithm,
+            size,
+            zoom,
+            level,
+            export,
+            replay,
+            name: name
+                .or_else(|| env::var("USER").ok())
+                .or_else(|| env::var("USERNAME").ok())
+                .unwrap_or_else(|| "Crabe".to_owned()),
+            enemies,
+        })
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(option: &str, value: Option<String>) -> GameResult<T> {
+    let value =
+        value.ok_or_else(|| GameError::ConfigError(format!("missing value for {}", option)))?;
+
+    value
+        .parse()
+        .map_err(|_| GameError::ConfigError(format!("invalid value {} for {}", value, option)))
+}
+
+/// Zooms the camera cannot reach are refused rather than giving an empty window
+fn parse_zoom(zoom: f32) -> GameResult<f32> {
+    if (MIN_ZOOM..=MAX_ZOOM).contains(&zoom) {
+        Ok(zoom)
+    } else {
+        Err(GameError::ConfigError(format!(
+            "invalid zoom {}, it must be between {} and {}",
+            zoom, MIN_ZOOM, MAX_ZOOM
+        )))
+    }
+}
+
+/// Parses `WIDTHxHEIGHT`, or a single number for a square maze. Both must be odd so that the maze
+/// is closed by walls, and at least 7 to hold the rewards.
+fn parse_size(value: &str) -> GameResult<(usize, usize)> {
+    let invalid = || GameError::ConfigError(format!("invalid maze size {}", value));
+
+    let mut parts = value.split('x').map(|part| part.parse::<usize>());
+    let size = match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(w)), None, None) => (w, w),
+        (Some(Ok(w)), Some(Ok(h)), None) => (w, h),
+        _ => return Err(invalid()),
+    };
+
+    if size.0 % 2 == 0 || size.1 % 2 == 0 || size.0 < 7 || size.1 < 7 {
+        return Err(invalid());
+    }
+    Ok(size)
+}
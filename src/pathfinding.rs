@@ -0,0 +1,94 @@
+//! Shortest paths on the maze grid
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::maze::{CellIndex, Direction, Maze, DIRECTIONS};
+
+/// A* search from `from` to `to` over the ground tiles accepted by `passable`, returns the cells
+/// to walk through, `from` excluded and `to` included
+pub fn find_path<F: Fn(CellIndex) -> bool>(
+    maze: &Maze,
+    from: CellIndex,
+    to: CellIndex,
+    passable: F,
+) -> Option<Vec<CellIndex>> {
+    let (w, h) = maze.dim();
+    let heuristic = |pos: CellIndex| pos.x.abs_diff(to.x) + pos.y.abs_diff(to.y);
+    let mut distances = vec![usize::MAX; w * h];
+    let mut came_from = vec![None; w * h];
+    let mut open = BinaryHeap::new();
+
+    distances[maze.index(from)] = 0;
+    open.push(Reverse((heuristic(from), 0, maze.index(from))));
+
+    while let Some(Reverse((_, distance, index))) = open.pop() {
+        let pos = CellIndex::from([index % w, index / w]);
+        if pos == to {
+            let mut path = vec![];
+            let mut current = index;
+            while current != maze.index(from) {
+                path.push(CellIndex::from([current % w, current / w]));
+                current = came_from[current].expect("reached cells but the start have a parent");
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if distance > distances[index] {
+            continue;
+        }
+
+        for &dir in &DIRECTIONS {
+            let next = match maze.neighbour(pos, dir) {
+                Some(next) if !maze.get(next).is_wall() && passable(next) => next,
+                _ => continue,
+            };
+            let next_index = maze.index(next);
+            if distance + 1 < distances[next_index] {
+                distances[next_index] = distance + 1;
+                came_from[next_index] = Some(index);
+                open.push(Reverse((
+                    distance + 1 + heuristic(next),
+                    distance + 1,
+                    next_index,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+/// Breadth-first search from `from` to `to` over the ground tiles accepted by `passable`, returns
+/// the same kind of path as `find_path`
+pub fn bfs_path<F: Fn(CellIndex) -> bool>(
+    maze: &Maze,
+    from: CellIndex,
+    to: CellIndex,
+    passable: F,
+) -> Option<Vec<CellIndex>> {
+    // walking down the distances to `to` always ends on it
+    let distances = maze.distances(to, passable);
+    let mut distance = distances[maze.index(from)]?;
+    let mut path = Vec::with_capacity(distance);
+    let mut current = from;
+
+    while distance > 0 {
+        current = DIRECTIONS
+            .iter()
+            .filter_map(|dir| maze.neighbour(current, *dir))
+            .find(|next| distances[maze.index(*next)] == Some(distance - 1))?;
+        path.push(current);
+        distance -= 1;
+    }
+
+    Some(path)
+}
+
+/// The direction leading from `from` to the neighbouring cell `to`
+pub fn direction_to(maze: &Maze, from: CellIndex, to: CellIndex) -> Option<Direction> {
+    DIRECTIONS
+        .iter()
+        .copied()
+        .find(|dir| maze.neighbour(from, *dir) == Some(to))
+}
@@ -1,52 +1,15 @@
-use std::collections::HashMap;
-use std::time::Duration;
-
-use ggez::{self, Context, GameResult};
-use ggez::audio::{SoundSource, Source};
-use ggez::graphics::{BlendMode, Drawable, DrawParam, Image, Rect};
-use ggez::nalgebra as na;
-use ggez::nalgebra::{Vector2, Point2};
-use ggez::timer;
-
-pub struct Animation<'a> {
-    frames: Vec<&'a Image>,
-    index: usize,
-    interval: Duration,
-}
+use serde::{Deserialize, Serialize};
 
-impl<'a> Animation<'a> {
-    pub fn new(frames: Vec<&'a Image>, interval: Duration) -> Animation<'a> {
-        Animation {
-            frames,
-            index: 0,
-            interval,
-        }
-    }
+use crate::maze::{CellIndex, Direction};
 
-    pub fn next(&mut self) {
-        self.index = (self.index + 1) % self.frames.len();
-    }
-}
-
-impl Drawable for Animation<'_> {
-    fn draw(&self, ctx: &mut Context, param: DrawParam) -> GameResult {
-        self.frames[self.index].draw(ctx, param)
-    }
-
-    fn dimensions(&self, _ctx: &mut Context) -> Option<Rect> {
-        Some(self.frames[self.index].dimensions())
-    }
+/// Number of ticks needed to walk from one cell to the next one
+pub const STEP_TICKS: u32 = 10;
+/// How long the hurt animation lasts, the player cannot move meanwhile
+pub const HURT_TICKS: u32 = 25;
+/// How long the player cannot be hurt again after a hit
+pub const INVULNERABLE_TICKS: u32 = 100;
 
-    fn set_blend_mode(&mut self, _mode: Option<BlendMode>) {
-        unimplemented!()
-    }
-
-    fn blend_mode(&self) -> Option<BlendMode> {
-        self.frames[self.index].blend_mode()
-    }
-}
-
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PlayerState {
     Idle,
     Run,
@@ -54,120 +17,297 @@ pub enum PlayerState {
     Dead,
 }
 
-pub struct Player<'a> {
-    pub pos: (f32, f32),
-    animations: HashMap<PlayerState, Animation<'a>>,
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Translation {
+    /// Where the translation starts, the player cell when not given
+    #[serde(default)]
+    from: Option<CellIndex>,
+    to: CellIndex,
+    progress: u32,
+    /// Pushed back after a hit rather than walking
+    knockback: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Player {
+    cell: CellIndex,
+    /// The cell walked from to reach `cell`, where a hit pushes the player back
+    previous: Option<CellIndex>,
     state: PlayerState,
-    last_animation_update_time: Duration,
-    last_movement_update_time: Duration,
-    current_translation: Option<(f32, f32, f32, f32)>,
+    current_translation: Option<Translation>,
     step_count: usize,
-    running_sound: Source,
     flipped: bool,
+    health: u8,
+    max_health: u8,
+    hurt_ticks: u32,
+    invulnerable_ticks: u32,
 }
 
-impl<'a> Player<'a> {
-    pub fn new(animations: HashMap<PlayerState, Animation>, mut running_sound: Source) -> Player {
-        running_sound.set_volume(0.3);
+impl Player {
+    pub fn new(start: CellIndex, health: u8) -> Player {
         Player {
-            pos: (1.0, 1.0), // start
-            animations,
+            cell: start,
+            previous: None,
             state: PlayerState::Idle,
-            last_animation_update_time: Duration::from_secs(0),
-            last_movement_update_time: Duration::from_secs(0),
             current_translation: None,
             step_count: 0,
-            running_sound,
             flipped: false,
+            health,
+            max_health: health,
+            hurt_ticks: 0,
+            invulnerable_ticks: 0,
         }
     }
 
-    pub fn translate(&mut self, vec: (f32, f32)) {
-        if self.state != PlayerState::Dead && self.current_translation.is_none() {
-            self.current_translation = Some((vec.0, vec.1, self.pos.0 + vec.0, self.pos.1 + vec.1));
-            self.state = PlayerState::Run;
-            if self.step_count % 2 == 0 {
-                self.running_sound.play();
+    /// The cell the player is standing on, a moving player is still on its departure cell
+    pub fn cell(&self) -> CellIndex {
+        self.cell
+    }
+
+    /// The cell being walked to, or the one the player stands on
+    pub fn destination(&self) -> CellIndex {
+        self.current_translation.map_or(self.cell, |t| t.to)
+    }
+
+    /// Every cell the player is on or may move to: the current one, the one walked from and the
+    /// one being walked to
+    pub fn cells(&self) -> impl Iterator<Item = CellIndex> {
+        self.sight_cells().chain(self.previous)
+    }
+
+    /// Cells the player sees from: the current one and, while moving, both ends of the move
+    pub fn sight_cells(&self) -> impl Iterator<Item = CellIndex> {
+        std::iter::once(self.cell)
+            .chain(self.current_translation.and_then(|t| t.from))
+            .chain(self.current_translation.map(|t| t.to))
+    }
+
+    /// Interpolated position, in cells
+    pub fn pos(&self) -> (f32, f32) {
+        match self.current_translation {
+            Some(translation) => {
+                let from = translation.from.unwrap_or(self.cell);
+                let (x, y) = (from.x as f32, from.y as f32);
+                let t = translation.progress as f32 / STEP_TICKS as f32;
+                (
+                    x + (translation.to.x as f32 - x) * t,
+                    y + (translation.to.y as f32 - y) * t,
+                )
             }
-            self.step_count += 1;
+            None => (self.cell.x as f32, self.cell.y as f32),
+        }
+    }
+
+    /// Starts walking one cell towards `dir`, does nothing if already moving, hurt or dead
+    pub fn translate(&mut self, dir: Direction) -> bool {
+        if self.state == PlayerState::Dead
+            || self.state == PlayerState::Hurt
+            || self.current_translation.is_some()
+        {
+            return false;
         }
+
+        let rel = dir.as_relative();
+        self.current_translation = Some(Translation {
+            from: None,
+            to: [
+                (self.cell.x as isize + rel.x) as usize,
+                (self.cell.y as isize + rel.y) as usize,
+            ]
+            .into(),
+            progress: 0,
+            knockback: false,
+        });
+        self.state = PlayerState::Run;
+        self.step_count += 1;
+        true
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.current_translation.is_some()
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.step_count
     }
 
     pub fn set_flipped(&mut self, flipped: bool) {
         self.flipped = flipped
     }
 
+    pub fn is_flipped(&self) -> bool {
+        self.flipped
+    }
+
     pub fn is_dead(&self) -> bool {
         self.state == PlayerState::Dead
     }
 
-    fn current_animation(&self) -> &Animation {
-        &self.animations[&self.state]
+    pub fn state(&self) -> PlayerState {
+        self.state
     }
 
     pub fn set_state(&mut self, state: PlayerState) {
         self.state = state;
     }
 
-    pub fn update(&mut self, ctx: &Context) {
-        let current_time = timer::time_since_start(ctx);
+    pub fn health(&self) -> u8 {
+        self.health
+    }
+
+    pub fn max_health(&self) -> u8 {
+        self.max_health
+    }
+
+    /// Ticks left before the player can be hurt again
+    pub fn invulnerable_ticks(&self) -> u32 {
+        self.invulnerable_ticks
+    }
 
-        // Animations
-        if current_time > self.last_animation_update_time + self.current_animation().interval {
-            self.animations.get_mut(&self.state).unwrap().next();
-            self.last_animation_update_time = current_time;
+    /// Takes `damage` and gets knocked back to the previous cell, or back where it came from when
+    /// hit on the way, unless invulnerable or dead. Returns whether the player was hit.
+    pub fn hurt(&mut self, damage: u8) -> bool {
+        if self.state == PlayerState::Dead || self.invulnerable_ticks > 0 {
+            return false;
         }
 
-        // Movement
-        if current_time > self.last_movement_update_time + Duration::from_millis(20)
-            && self.state != PlayerState::Dead
-            && self.state != PlayerState::Hurt
-        {
-            if let Some(translation) = self.current_translation {
-                if translation.2 <= self.pos.0 + 0.1
-                    && translation.2 >= self.pos.0 - 0.1
-                    && translation.3 <= self.pos.1 + 0.1
-                    && translation.3 >= self.pos.1 - 0.1
-                {
-                    self.pos = (translation.2, translation.3);
-                    self.state = PlayerState::Idle;
-                    self.current_translation = None;
+        self.health = self.health.saturating_sub(damage);
+        if self.health == 0 {
+            self.state = PlayerState::Dead;
+            self.current_translation = None;
+            return true;
+        }
+
+        self.state = PlayerState::Hurt;
+        self.hurt_ticks = HURT_TICKS;
+        self.invulnerable_ticks = INVULNERABLE_TICKS;
+        self.current_translation = match self.current_translation {
+            // the step is walked back from where the player was hit
+            Some(step) => Some(Translation {
+                from: Some(step.to),
+                to: self.cell,
+                progress: STEP_TICKS - step.progress,
+                knockback: true,
+            }),
+            None => self.previous.map(|to| Translation {
+                from: None,
+                to,
+                progress: 0,
+                knockback: true,
+            }),
+        };
+        self.previous = None;
+        true
+    }
+
+    /// Advances the current movement and the hurt timers by one tick
+    pub fn update(&mut self) {
+        if self.state == PlayerState::Dead {
+            return;
+        }
+
+        self.invulnerable_ticks = self.invulnerable_ticks.saturating_sub(1);
+        if self.hurt_ticks > 0 {
+            self.hurt_ticks -= 1;
+            if self.hurt_ticks == 0 {
+                self.state = PlayerState::Idle;
+            }
+        }
+
+        if let Some(translation) = &mut self.current_translation {
+            translation.progress += 1;
+
+            if translation.progress >= STEP_TICKS {
+                self.previous = if translation.knockback {
+                    None
                 } else {
-                    self.pos = (
-                        self.pos.0 + translation.0 * 0.1,
-                        self.pos.1 + translation.1 * 0.1,
-                    );
+                    Some(self.cell)
+                };
+                self.cell = translation.to;
+                self.current_translation = None;
+                if self.state == PlayerState::Run {
+                    self.state = PlayerState::Idle;
                 }
-                self.last_movement_update_time = current_time;
             }
         }
     }
 }
 
-impl Drawable for Player<'_> {
-    fn draw(&self, ctx: &mut Context, param: DrawParam) -> GameResult {
-        let current_animation = self.current_animation();
-        current_animation.draw(
-            ctx,
-            param.clone().offset(Point2::new(0.5, 0.)).scale(Vector2::new(if self.flipped { -1. } else { 1. }, 1.)).dest(na::Point2::new(
-                param.dest.x + self.pos.0 * 32.0 + 16.,
-                param.dest.y + self.pos.1 * 32.0,
-            )),
-        )
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(player: &mut Player, ticks: u32) {
+        for _ in 0..ticks {
+            player.update();
+        }
+    }
+
+    fn walked_east() -> Player {
+        let mut player = Player::new([1, 1].into(), 3);
+        assert!(player.translate(Direction::East));
+        update(&mut player, STEP_TICKS);
+        assert_eq!(player.cell(), [2, 1].into());
+        player
     }
 
-    fn dimensions(&self, ctx: &mut Context) -> Option<Rect> {
-        self.current_animation().dimensions(ctx)
+    #[test]
+    fn hits_are_ignored_while_invulnerable() {
+        let mut player = walked_east();
+
+        assert!(player.hurt(1));
+        update(&mut player, INVULNERABLE_TICKS - 1);
+        assert!(!player.hurt(1));
+        assert_eq!(player.health(), 2);
+
+        update(&mut player, 1);
+        assert!(player.hurt(1));
+        assert_eq!(player.health(), 1);
+    }
+
+    #[test]
+    fn hits_knock_back_to_the_previous_cell() {
+        let mut player = walked_east();
+
+        assert!(player.hurt(1));
+        assert_eq!(player.state(), PlayerState::Hurt);
+        assert!(!player.translate(Direction::East));
+        update(&mut player, STEP_TICKS);
+        assert_eq!(player.cell(), [1, 1].into());
+        assert!(!player.is_moving());
+
+        update(&mut player, HURT_TICKS - STEP_TICKS);
+        assert_eq!(player.state(), PlayerState::Idle);
+        // a knockback is not a step to be pushed back from again
+        update(&mut player, INVULNERABLE_TICKS);
+        assert!(player.hurt(1));
+        assert!(!player.is_moving());
     }
 
-    fn set_blend_mode(&mut self, mode: Option<BlendMode>) {
-        self.animations
-            .get_mut(&self.state)
-            .unwrap()
-            .set_blend_mode(mode);
+    #[test]
+    fn hits_on_the_way_walk_the_step_back() {
+        let mut player = Player::new([1, 1].into(), 3);
+        player.translate(Direction::East);
+        update(&mut player, 4);
+        let pos = player.pos();
+
+        assert!(player.hurt(1));
+        assert_eq!(player.pos(), pos);
+        update(&mut player, 4);
+        assert_eq!(player.cell(), [1, 1].into());
+        assert!(!player.is_moving());
     }
 
-    fn blend_mode(&self) -> Option<BlendMode> {
-        self.current_animation().blend_mode()
+    #[test]
+    fn death_comes_only_at_zero_health() {
+        let mut player = Player::new([1, 1].into(), 2);
+
+        assert!(player.hurt(1));
+        assert!(!player.is_dead());
+        update(&mut player, INVULNERABLE_TICKS);
+        assert!(player.hurt(1));
+        assert!(player.is_dead());
+        assert!(!player.hurt(1));
+        assert!(!player.translate(Direction::East));
     }
 }
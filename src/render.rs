@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Duration;
+
+use ggez::graphics::{
+    self, BlendMode, Color, DrawMode, DrawParam, Drawable, Image, MeshBuilder, Rect, BLACK,
+};
+use ggez::nalgebra::{Point2, Vector2};
+use ggez::{Context, GameResult};
+
+use crate::assets::Assets;
+use crate::camera::Camera;
+use crate::enemy::Enemy;
+use crate::game::TICKS_PER_SECOND;
+use crate::layout::TILE_SIZE;
+use crate::maze::{CellIndex, Direction, Maze};
+use crate::player::{Player, PlayerState};
+use crate::vision::Vision;
+
+pub struct Animation<'a> {
+    frames: Vec<&'a Image>,
+    index: usize,
+    interval: Duration,
+}
+
+impl<'a> Animation<'a> {
+    pub fn new(frames: Vec<&'a Image>, interval: Duration) -> Animation<'a> {
+        Animation {
+            frames,
+            index: 0,
+            interval,
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.index = (self.index + 1) % self.frames.len();
+    }
+}
+
+impl Drawable for Animation<'_> {
+    fn draw(&self, ctx: &mut Context, param: DrawParam) -> GameResult {
+        self.frames[self.index].draw(ctx, param)
+    }
+
+    fn dimensions(&self, _ctx: &mut Context) -> Option<Rect> {
+        Some(self.frames[self.index].dimensions())
+    }
+
+    fn set_blend_mode(&mut self, _mode: Option<BlendMode>) {
+        unimplemented!()
+    }
+
+    fn blend_mode(&self) -> Option<BlendMode> {
+        self.frames[self.index].blend_mode()
+    }
+}
+
+/// Draws a `Player`, animating it according to its state
+pub struct PlayerRenderer<'a> {
+    animations: HashMap<PlayerState, Animation<'a>>,
+    state: PlayerState,
+    /// Game time of the last frame change
+    last_animation_update_time: Duration,
+}
+
+impl<'a> PlayerRenderer<'a> {
+    pub fn new(images: &'a Assets<Image>) -> PlayerRenderer<'a> {
+        let mut animations = HashMap::new();
+        animations.insert(
+            PlayerState::Idle,
+            Animation::new(
+                images.get_from_pattern("game/idle_*.png"),
+                Duration::from_millis(150),
+            ),
+        );
+        animations.insert(
+            PlayerState::Run,
+            Animation::new(
+                images.get_from_pattern("game/run_*.png"),
+                Duration::from_millis(125),
+            ),
+        );
+        animations.insert(
+            PlayerState::Hurt,
+            Animation::new(
+                images.get_from_pattern("game/hurt_*.png"),
+                Duration::from_millis(125),
+            ),
+        );
+        animations.insert(
+            PlayerState::Dead,
+            Animation::new(
+                images.get_from_pattern("game/death_*.png"),
+                Duration::from_millis(125),
+            ),
+        );
+
+        PlayerRenderer {
+            animations,
+            state: PlayerState::Idle,
+            last_animation_update_time: Duration::from_secs(0),
+        }
+    }
+
+    fn current_animation(&self) -> &Animation<'a> {
+        &self.animations[&self.state]
+    }
+
+    /// Animates on the game clock, `tick` being the current tick, so that a paused game stays
+    /// still
+    pub fn update(&mut self, player: &Player, tick: u64) {
+        let current_time = Duration::from_millis(tick * 1000 / TICKS_PER_SECOND as u64);
+        self.state = player.state();
+
+        if current_time > self.last_animation_update_time + self.current_animation().interval {
+            self.animations.get_mut(&self.state).unwrap().next();
+            self.last_animation_update_time = current_time;
+        }
+    }
+
+    /// Draws the player tinted by the color of `param`, blinking while it is invulnerable
+    pub fn draw(&self, ctx: &mut Context, player: &Player, param: DrawParam) -> GameResult {
+        let (x, y) = player.pos();
+        let blinking = player.state() != PlayerState::Dead
+            && player.invulnerable_ticks() > 0
+            && (player.invulnerable_ticks() / 5).is_multiple_of(2);
+        let param = if blinking {
+            param.color(Color {
+                a: param.color.a * 0.4,
+                ..param.color
+            })
+        } else {
+            param
+        };
+
+        draw_crab(
+            ctx,
+            self.current_animation(),
+            (x, y),
+            player.is_flipped(),
+            param,
+        )
+    }
+}
+
+/// Names of the enemy animation sets, in `game/enemies`
+const ENEMY_KINDS: [&str; 2] = ["red", "purple"];
+/// Time between two frames of the enemy animations, all kept in step
+const ENEMY_FRAME_INTERVAL: Duration = Duration::from_millis(125);
+
+/// Draws the `Enemy`s, each with the animation set of its kind
+pub struct EnemyRenderer<'a> {
+    /// (idle, run) animations of each kind
+    animations: Vec<(Animation<'a>, Animation<'a>)>,
+    /// Game time of the last frame change
+    last_animation_update_time: Duration,
+}
+
+impl<'a> EnemyRenderer<'a> {
+    pub fn new(images: &'a Assets<Image>) -> EnemyRenderer<'a> {
+        let animations = ENEMY_KINDS
+            .iter()
+            .map(|kind| {
+                let frames = |name| {
+                    images.get_from_pattern(&format!("game/enemies/{}/{}_*.png", kind, name))
+                };
+                (
+                    Animation::new(frames("idle"), ENEMY_FRAME_INTERVAL),
+                    Animation::new(frames("run"), ENEMY_FRAME_INTERVAL),
+                )
+            })
+            .collect();
+
+        EnemyRenderer {
+            animations,
+            last_animation_update_time: Duration::from_secs(0),
+        }
+    }
+
+    /// Animates on the game clock, like `PlayerRenderer::update`
+    pub fn update(&mut self, tick: u64) {
+        let current_time = Duration::from_millis(tick * 1000 / TICKS_PER_SECOND as u64);
+
+        if current_time > self.last_animation_update_time + ENEMY_FRAME_INTERVAL {
+            for (idle, run) in &mut self.animations {
+                idle.next();
+                run.next();
+            }
+            self.last_animation_update_time = current_time;
+        }
+    }
+
+    pub fn draw<'e, I: IntoIterator<Item = &'e Enemy>>(
+        &self,
+        ctx: &mut Context,
+        enemies: I,
+        param: DrawParam,
+    ) -> GameResult {
+        for enemy in enemies {
+            let (idle, run) = &self.animations[enemy.kind() % self.animations.len()];
+            let animation = if enemy.is_moving() { run } else { idle };
+            draw_crab(ctx, animation, enemy.pos(), enemy.is_flipped(), param)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws a crab standing at `(x, y)`, in cells, facing left when `flipped`
+fn draw_crab(
+    ctx: &mut Context,
+    animation: &Animation,
+    (x, y): (f32, f32),
+    flipped: bool,
+    param: DrawParam,
+) -> GameResult {
+    animation.draw(
+        ctx,
+        param
+            .offset(Point2::new(0.5, 0.))
+            .scale(Vector2::new(
+                if flipped { -1. } else { 1. } * param.scale.x,
+                param.scale.y,
+            ))
+            .dest(Point2::new(
+                param.dest.x + (x * TILE_SIZE + TILE_SIZE / 2.) * param.scale.x,
+                param.dest.y + y * TILE_SIZE * param.scale.y,
+            )),
+    )
+}
+
+/// Draws a `Maze` and its rewards, wall textures are picked once from the neighbouring walls
+pub struct MazeRenderer {
+    grass: Image,
+    walls: Vec<Option<Image>>,
+    apple: Image,
+    death: Image,
+    empty: Image,
+}
+
+impl MazeRenderer {
+    pub fn new(maze: &Maze, images: &Assets<Image>) -> MazeRenderer {
+        let (w, h) = maze.dim();
+        let mut walls = Vec::with_capacity(w * h);
+
+        for y in 0..h {
+            for x in 0..w {
+                walls.push(wall_texture(maze, [x, y].into(), images));
+            }
+        }
+
+        MazeRenderer {
+            grass: images["/game/grass.png"].clone(),
+            walls,
+            apple: images["/game/pan_apple.png"].clone(),
+            death: images["/game/pan_death.png"].clone(),
+            empty: images["/game/pan_empty.png"].clone(),
+        }
+    }
+
+    /// Draws the visible columns and rows of the maze, rewards are drawn as empty pans when
+    /// `hidden` is set, but for the maluses the player stepped on
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        maze: &Maze,
+        hidden: bool,
+        (columns, rows): (Range<usize>, Range<usize>),
+        param: DrawParam,
+    ) -> GameResult {
+        const WALL_SCALING: f32 = 1.2;
+        const REWARD_SCALING: f32 = 0.8;
+
+        let (w, h) = maze.dim();
+        let columns = columns.start..columns.end.min(w);
+        let rows = rows.start..rows.end.min(h);
+
+        for x in columns.clone() {
+            for y in rows.clone() {
+                let param2 = param.dest(Point2::new(
+                    param.dest.x + x as f32 * TILE_SIZE * param.scale.x,
+                    param.dest.y + y as f32 * TILE_SIZE * param.scale.y,
+                ));
+
+                self.grass.draw(ctx, param2)?;
+
+                if let Some(img) = &self.walls[y * w + x] {
+                    img.draw(
+                        ctx,
+                        param2
+                            .scale([param2.scale.x * WALL_SCALING, param2.scale.y * WALL_SCALING])
+                            .dest([
+                                param2.dest.x
+                                    - (TILE_SIZE * WALL_SCALING - TILE_SIZE) * param2.scale.x / 2.,
+                                param2.dest.y
+                                    - (TILE_SIZE * WALL_SCALING - TILE_SIZE) * param2.scale.y / 2.,
+                            ]),
+                    )?;
+                }
+            }
+        }
+
+        for r in maze.rewards() {
+            if (r.found && !r.malus) || !columns.contains(&r.pos().x) || !rows.contains(&r.pos().y)
+            {
+                continue;
+            }
+
+            let texture = if hidden && !r.found {
+                &self.empty
+            } else if r.malus {
+                &self.death
+            } else {
+                &self.apple
+            };
+
+            texture.draw(
+                ctx,
+                param
+                    .scale([
+                        param.scale.x * REWARD_SCALING,
+                        param.scale.y * REWARD_SCALING,
+                    ])
+                    .dest([
+                        param.dest.x + r.pos().x as f32 * TILE_SIZE * param.scale.x
+                            - (TILE_SIZE * REWARD_SCALING - TILE_SIZE) * param.scale.x / 2.,
+                        param.dest.y + (r.pos().y as f32 * TILE_SIZE - 24.) * param.scale.y
+                            - (TILE_SIZE * REWARD_SCALING - TILE_SIZE) * param.scale.y / 2.,
+                    ]),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Marks the cells of a hint with dots, fading away along the route
+pub fn draw_hint(ctx: &mut Context, camera: &Camera, cells: &[CellIndex]) -> GameResult {
+    if cells.is_empty() {
+        return Ok(());
+    }
+
+    let radius = TILE_SIZE * camera.zoom() / 6.;
+    let mut mesh = MeshBuilder::new();
+    for (i, cell) in cells.iter().enumerate() {
+        let center = camera.cell_to_screen((cell.x as f32 + 0.5, cell.y as f32 + 0.5));
+        let alpha = 1. - i as f32 / cells.len() as f32 * 0.7;
+        mesh.circle(
+            DrawMode::fill(),
+            center,
+            radius,
+            0.5,
+            Color::new(1., 0.85, 0.2, alpha),
+        );
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::new())
+}
+
+/// How dark explored cells out of sight are, from 0 to 1
+const EXPLORED_DARKNESS: f32 = 0.6;
+
+/// Covers the cells in view that the player never saw with black, and darkens the ones out of
+/// sight
+pub fn draw_fog(ctx: &mut Context, camera: &Camera, maze: &Maze, vision: &Vision) -> GameResult {
+    let (w, h) = maze.dim();
+    let (columns, rows) = camera.visible_cells();
+    let size = TILE_SIZE * camera.zoom();
+    let explored = Color::new(0., 0., 0., EXPLORED_DARKNESS);
+
+    let mut mesh = MeshBuilder::new();
+    let mut empty = true;
+    for y in rows.start..rows.end.min(h) {
+        let fog: Vec<_> = (columns.start..columns.end.min(w))
+            .map(|x| match CellIndex::from([x, y]) {
+                pos if vision.is_visible(maze, pos) => None,
+                pos if vision.is_explored(maze, pos) => Some(explored),
+                _ => Some(BLACK),
+            })
+            .collect();
+
+        // neighbouring cells under the same fog make a single rectangle
+        let mut x = columns.start;
+        for run in fog.chunk_by(|a, b| a == b) {
+            if let Some(color) = run[0] {
+                let corner = camera.cell_to_screen((x as f32, y as f32));
+                let rect = Rect::new(corner.x, corner.y, run.len() as f32 * size, size);
+                mesh.rectangle(DrawMode::fill(), rect, color);
+                empty = false;
+            }
+            x += run.len();
+        }
+    }
+
+    if empty {
+        return Ok(());
+    }
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::new())
+}
+
+fn wall_texture(maze: &Maze, index: CellIndex, images: &Assets<Image>) -> Option<Image> {
+    if !maze.get(index).is_wall() {
+        return None;
+    }
+
+    let is_wall = |dir| matches!(maze.get_tile_rel(index, dir), Some(tile) if tile.is_wall());
+
+    let name = match (
+        is_wall(Direction::South),
+        is_wall(Direction::East),
+        is_wall(Direction::North),
+        is_wall(Direction::West),
+    ) {
+        (true, true, false, false) => "/game/wall_corn_top_lft.png",
+        (true, false, false, true) => "/game/wall_corn_top_rgt.png",
+        (false, true, true, false) => "/game/wall_corn_bot_lft.png",
+        (false, false, true, true) => "/game/wall_corn_bot_rgt.png",
+
+        (true, true, true, true) => "/game/wall_crss_all.png",
+        (true, true, false, true) => "/game/wall_crss_hori_top.png",
+        (false, true, true, true) => "/game/wall_crss_hori_bot.png",
+        (true, true, true, false) => "/game/wall_crss_vert_lft.png",
+        (true, false, true, true) => "/game/wall_crss_vert_rgt.png",
+
+        (false, false, false, true) => "/game/wall_hori_rgt.png",
+        (false, true, false, true) => "/game/wall_hori_mid.png",
+        (false, true, false, false) => "/game/wall_hori_lft.png",
+
+        (true, false, false, false) => "/game/wall_vert_top.png",
+        (true, false, true, false) => "/game/wall_vert_mid.png",
+        (false, false, true, false) => "/game/wall_vert_bot.png",
+
+        (false, false, false, false) => return None,
+    };
+
+    Some(images[name].clone())
+}
@@ -0,0 +1,270 @@
+//! Recorded games. The simulation being deterministic, the maze, the rules and the commands given
+//! at each tick are enough to play a game again exactly. Commands are only written when they
+//! change, which keeps the files small.
+
+use std::io::Write;
+
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::formats::FormatError;
+use crate::game::{Command, Game, Outcome, Rules};
+use crate::maze::{Direction, Maze};
+use crate::storage;
+
+/// Where the last game played is kept, in the user directory
+const LAST_REPLAY_PATH: &str = "/replay.json";
+/// Bumped whenever the file layout changes, or the simulation so that recorded games would play
+/// differently
+const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    seed: Option<u64>,
+    rules: Rules,
+    /// The maze as an ASCII grid
+    maze: String,
+    /// Ticks at which the command changed, with the new command
+    #[serde(with = "compact_inputs")]
+    inputs: Vec<(u64, Option<Command>)>,
+    /// Number of ticks recorded
+    length: u64,
+    /// How the game ended, if it did
+    #[serde(default)]
+    outcome: Option<Outcome>,
+}
+
+impl Replay {
+    /// Starts recording `game`, which cannot be done once it started or when some rewards are
+    /// already found
+    pub fn new(game: &Game) -> Option<Replay> {
+        if game.tick_count() > 0 || game.maze().rewards().iter().any(|r| r.found) {
+            return None;
+        }
+
+        Some(Replay {
+            version: VERSION,
+            seed: game.maze().seed(),
+            rules: game.rules().clone(),
+            maze: game.maze().to_ascii(),
+            inputs: vec![],
+            length: 0,
+            outcome: None,
+        })
+    }
+
+    /// Records that `command` is given to the game at `tick`, ticks coming in order
+    pub fn record(&mut self, tick: u64, command: Option<Command>) {
+        if self
+            .inputs
+            .last()
+            .map_or(command.is_some(), |(_, last)| *last != command)
+        {
+            self.inputs.push((tick, command));
+        }
+        self.length = self.length.max(tick + 1);
+    }
+
+    /// Records how the game ended
+    pub fn finish(&mut self, outcome: Outcome) {
+        self.outcome = Some(outcome);
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// Whether the recorded game was played on the same maze and rules as `game`
+    pub fn matches(&self, game: &Game) -> bool {
+        self.seed == game.maze().seed()
+            && self.rules == *game.rules()
+            && self.maze == game.maze().to_ascii()
+    }
+
+    /// The command given at `tick`
+    pub fn command_at(&self, tick: u64) -> Option<Command> {
+        let next = self.inputs.partition_point(|(t, _)| *t <= tick);
+        next.checked_sub(1).and_then(|i| self.inputs[i].1)
+    }
+
+    /// Number of ticks recorded
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// The game as it was when the recording started
+    pub fn game(&self) -> Result<Game, FormatError> {
+        let mut maze = Maze::from_ascii(&self.maze)?;
+        maze.set_seed(self.seed);
+        Ok(Game::new(maze, self.rules.clone()))
+    }
+
+    /// Keeps the replay as the last game played
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        filesystem::create(ctx, LAST_REPLAY_PATH)?.write_all(self.to_json().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a replay always serializes")
+    }
+
+    pub fn from_json(text: &str) -> GameResult<Replay> {
+        let invalid =
+            |reason: String| GameError::ConfigError(format!("invalid replay: {}", reason));
+
+        let replay: Replay = storage::parse(text, VERSION).map_err(invalid)?;
+        replay.game()?;
+
+        Ok(replay)
+    }
+}
+
+/// Writes the inputs as a string such as `"12N 30- 45E 60H"`: a tick then `N`, `E`, `S` or `W` to
+/// move that way, `H` for a hint or `-` to stop
+mod compact_inputs {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{Command, Direction};
+
+    pub fn serialize<S: Serializer>(
+        inputs: &[(u64, Option<Command>)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let tokens: Vec<_> = inputs
+            .iter()
+            .map(|(tick, command)| {
+                let letter = match command {
+                    Some(Command::Move(Direction::North)) => 'N',
+                    Some(Command::Move(Direction::East)) => 'E',
+                    Some(Command::Move(Direction::South)) => 'S',
+                    Some(Command::Move(Direction::West)) => 'W',
+                    Some(Command::Hint) => 'H',
+                    None => '-',
+                };
+                format!("{}{}", tick, letter)
+            })
+            .collect();
+
+        serializer.serialize_str(&tokens.join(" "))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(u64, Option<Command>)>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let mut inputs: Vec<(u64, Option<Command>)> = vec![];
+
+        for token in text.split_whitespace() {
+            let invalid = || D::Error::custom(format!("invalid input {:?}", token));
+            if !token.is_ascii() {
+                return Err(invalid());
+            }
+            let (tick, letter) = token.split_at(token.len() - 1);
+            let tick: u64 = tick.parse().map_err(|_| invalid())?;
+            let command = match letter {
+                "N" => Some(Command::Move(Direction::North)),
+                "E" => Some(Command::Move(Direction::East)),
+                "S" => Some(Command::Move(Direction::South)),
+                "W" => Some(Command::Move(Direction::West)),
+                "H" => Some(Command::Hint),
+                "-" => None,
+                _ => return Err(invalid()),
+            };
+            if inputs.last().is_some_and(|(last, _)| *last >= tick) {
+                return Err(D::Error::custom("inputs are not in order"));
+            }
+            inputs.push((tick, command));
+        }
+
+        Ok(inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::generator::Algorithm;
+    use crate::maze::{MazeRng, DIRECTIONS};
+
+    fn new_game(seed: u64) -> Game {
+        let maze = Maze::from_seed((15, 15), seed, &*Algorithm::Prim.generator()).unwrap();
+        let rules = Rules {
+            preview_ticks: 20,
+            enemies: 2,
+            ..Rules::for_maze(&maze)
+        };
+        Game::new(maze, rules)
+    }
+
+    /// A command held for a few ticks at a time, hints now and then
+    fn random_commands(seed: u64) -> impl Iterator<Item = Option<Command>> {
+        let mut rng = MazeRng::seed_from_u64(seed);
+        std::iter::repeat_with(move || {
+            let command = match rng.gen_range(0, 12) {
+                0 => Some(Command::Hint),
+                1..=3 => None,
+                _ => Some(Command::Move(DIRECTIONS[rng.gen_range(0, 4)])),
+            };
+            std::iter::repeat_n(command, rng.gen_range(1, 30))
+        })
+        .flatten()
+    }
+
+    #[test]
+    fn replays_play_the_same_game() {
+        for seed in 0..10 {
+            let mut game = new_game(seed);
+            let mut replay = Replay::new(&game).unwrap();
+            for command in random_commands(seed).take(3000) {
+                if game.outcome().is_some() {
+                    break;
+                }
+                replay.record(game.tick_count(), command);
+                game.tick(command);
+            }
+            if let Some(outcome) = game.outcome() {
+                replay.finish(outcome);
+            }
+
+            let replay = Replay::from_json(&replay.to_json()).unwrap();
+            let mut replayed = replay.game().unwrap();
+            assert!(replay.matches(&replayed));
+            while replayed.tick_count() < replay.length() && replayed.outcome().is_none() {
+                replayed.tick(replay.command_at(replayed.tick_count()));
+            }
+
+            assert_eq!(replayed.outcome(), replay.outcome());
+            assert_eq!(
+                serde_json::to_string(&replayed).unwrap(),
+                serde_json::to_string(&game).unwrap(),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn commands_are_only_kept_when_they_change() {
+        let mut replay = Replay::new(&new_game(0)).unwrap();
+        let east = Some(Command::Move(Direction::East));
+        for (tick, command) in [None, east, east, east, None, None, Some(Command::Hint)]
+            .iter()
+            .enumerate()
+        {
+            replay.record(tick as u64, *command);
+        }
+
+        assert_eq!(
+            replay.inputs,
+            vec![(1, east), (4, None), (6, Some(Command::Hint))]
+        );
+        assert_eq!(replay.command_at(3), east);
+        assert_eq!(replay.command_at(5), None);
+        assert_eq!(replay.length(), 7);
+    }
+}
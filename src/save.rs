@@ -0,0 +1,68 @@
+//! A game in progress, kept in the user directory when the window closes so that it can be
+//! resumed from the title screen. Only the logic is saved, the renderers are made again from the
+//! loaded images when the game resumes.
+
+use std::io::Write;
+
+use ggez::{filesystem, Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+use crate::game::Game;
+use crate::scenes::Mode;
+use crate::storage;
+
+/// Where the game is kept, in the user directory
+const SAVE_PATH: &str = "/savegame.json";
+/// Bumped whenever the file layout changes
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    version: u32,
+    pub mode: Mode,
+    /// The game as it was when it started, to restart it
+    pub initial: Game,
+    pub game: Game,
+}
+
+impl SaveGame {
+    pub fn new(mode: Mode, initial: Game, game: Game) -> SaveGame {
+        SaveGame {
+            version: VERSION,
+            mode,
+            initial,
+            game,
+        }
+    }
+
+    /// Reads the saved game, if there is one
+    pub fn load(ctx: &mut Context) -> Option<SaveGame> {
+        storage::load_checked(
+            ctx,
+            SAVE_PATH,
+            VERSION,
+            "the saved game",
+            |save: &SaveGame| {
+                if save.game.is_consistent() && save.initial.is_consistent() {
+                    Ok(())
+                } else {
+                    Err("positions outside the maze".to_owned())
+                }
+            },
+        )
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let text = serde_json::to_string(self).expect("a game always serializes");
+        filesystem::create(ctx, SAVE_PATH)?.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    /// Removes the saved game, once resumed
+    pub fn delete(ctx: &mut Context) -> GameResult {
+        if filesystem::exists(ctx, SAVE_PATH) {
+            filesystem::delete(ctx, SAVE_PATH)?;
+        }
+        Ok(())
+    }
+}
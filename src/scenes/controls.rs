@@ -0,0 +1,140 @@
+use ggez::event::Button;
+use ggez::graphics::{Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use super::{Scene, Shared, Title, Transition, MENU_MUSIC};
+use crate::controls::{self, Controls};
+use crate::gamepad;
+use crate::menu::Menu;
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Bind(controls::Action),
+    Reset,
+    Back,
+}
+
+/// Lists the keys of every action, choosing one waits for its new key
+pub struct ControlsMenu {
+    title: Text,
+    menu: Menu<Action>,
+    /// Action waiting for a key
+    binding: Option<controls::Action>,
+    /// Key pressed while an action was waiting for one
+    pressed: Option<KeyCode>,
+    back: bool,
+}
+
+impl ControlsMenu {
+    pub fn new(shared: &Shared) -> ControlsMenu {
+        let mut items: Vec<_> = controls::Action::ALL
+            .iter()
+            .map(|action| (action.label(), Action::Bind(*action)))
+            .collect();
+        items.push(("Reset", Action::Reset));
+        items.push(("Back", Action::Back));
+
+        ControlsMenu {
+            title: controls_text(&shared.controls, "Choose an action to change its key"),
+            menu: Menu::new(&items),
+            binding: None,
+            pressed: None,
+            back: false,
+        }
+    }
+}
+
+fn save(ctx: &mut Context, controls: &Controls) {
+    if let Err(e) = controls.save(ctx) {
+        eprintln!("could not save the controls: {}", e);
+    }
+}
+
+/// The title, the keys of every action and `message`
+fn controls_text(controls: &Controls, message: &str) -> Text {
+    let mut lines = "\n\n".to_owned();
+    for action in &controls::Action::ALL {
+        lines += &format!("{}: {}\n", action.label(), controls.describe(*action));
+    }
+    lines += &format!("\n{}", message);
+
+    let mut text = super::title_text("Controls");
+    text.add(TextFragment::new(lines).scale(Scale::uniform(14.)));
+    text
+}
+
+impl<'a> Scene<'a> for ControlsMenu {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        shared.play_music(ctx, MENU_MUSIC)?;
+
+        if self.back {
+            return Ok(Transition::Switch(Box::new(Title::new(shared, None))));
+        }
+
+        let message = match (self.binding, self.pressed.take()) {
+            (Some(_), Some(KeyCode::Escape)) => {
+                self.binding = None;
+                Some("Cancelled".to_owned())
+            }
+            (Some(action), Some(key)) => match shared.controls.bind(action, key) {
+                Ok(()) => {
+                    self.binding = None;
+                    save(ctx, &shared.controls);
+                    Some(format!("{} is now {:?}", action.label(), key))
+                }
+                // the action keeps waiting for another key
+                Err(other) => Some(format!(
+                    "{:?} is already used by {}, press another key",
+                    key,
+                    other.label()
+                )),
+            },
+            _ => match self.menu.take_chosen() {
+                Some(Action::Bind(action)) => {
+                    self.binding = Some(action);
+                    Some(format!(
+                        "Press a key for {}, Escape or B to cancel",
+                        action.label()
+                    ))
+                }
+                Some(Action::Reset) => {
+                    shared.controls = Controls::default();
+                    save(ctx, &shared.controls);
+                    Some("Default keys restored".to_owned())
+                }
+                Some(Action::Back) => {
+                    return Ok(Transition::Switch(Box::new(Title::new(shared, None))))
+                }
+                None => None,
+            },
+        };
+
+        if let Some(message) = message {
+            self.title = controls_text(&shared.controls, &message);
+        }
+        Ok(Transition::None)
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        match (self.binding, keycode) {
+            (Some(_), _) => self.pressed = Some(keycode),
+            (None, KeyCode::Escape) => self.back = true,
+            (None, _) => self.menu.key_down(keycode),
+        }
+    }
+
+    /// Only keys can be bound, a gamepad may still cancel
+    fn button_down(&mut self, ctx: &mut Context, button: Button) {
+        match (self.binding, gamepad::menu_key(button)) {
+            (Some(_), Some(KeyCode::Escape)) => self.pressed = Some(KeyCode::Escape),
+            (Some(_), _) => {}
+            (None, Some(keycode)) => self.key_down(ctx, keycode),
+            (None, None) => {}
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        super::draw_menu_screen(ctx, shared, &self.title, &self.menu, false)
+    }
+}
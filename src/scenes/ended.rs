@@ -0,0 +1,131 @@
+use ggez::graphics::{Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use super::{Mode, Playing, Replaying, Scene, Shared, Title, Transition, MENU_MUSIC};
+use crate::game::{Game, Outcome};
+use crate::menu::Menu;
+use crate::score::Breakdown;
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    NextLevel,
+    Retry,
+    PlayAgain,
+    WatchReplay,
+    Title,
+    Quit,
+}
+
+/// Victory or game over screen, drawn over the finished game, with the score of won games
+pub struct Ended {
+    title: Text,
+    menu: Menu<Action>,
+    /// The finished game as it was when it started, to race its ghost
+    initial: Game,
+    mode: Mode,
+}
+
+impl Ended {
+    /// `rank` is where the game made it in the leaderboard, from 0
+    pub fn new(
+        shared: &Shared,
+        outcome: Outcome,
+        initial: Game,
+        mode: Mode,
+        score: Breakdown,
+        rank: Option<usize>,
+    ) -> Ended {
+        let title = match outcome {
+            Outcome::Won => "Gagné OwO",
+            Outcome::Lost => "Perdu UwU",
+        };
+
+        let mut items = vec![
+            ("Retry", Action::Retry),
+            ("Play again", Action::PlayAgain),
+            ("Title", Action::Title),
+            ("Quit", Action::Quit),
+        ];
+        if shared.replay.is_some() {
+            items.insert(2, ("Watch replay", Action::WatchReplay));
+        }
+        if let (Outcome::Won, Mode::Campaign(level)) = (outcome, mode) {
+            if level + 1 < shared.campaign.levels.len() {
+                items.insert(0, ("Next level", Action::NextLevel));
+            }
+        }
+
+        let mut title = super::title_text(title);
+        if outcome == Outcome::Won {
+            let mut lines = score_lines(&score);
+            if let Some(rank) = rank {
+                lines += &format!("\nHigh score, #{}!", rank + 1);
+            }
+            title.add(TextFragment::new(lines).scale(Scale::uniform(18.)));
+        }
+
+        Ended {
+            title,
+            menu: Menu::new(&items),
+            initial,
+            mode,
+        }
+    }
+}
+
+fn score_lines(score: &Breakdown) -> String {
+    let hints = if score.hints > 0 {
+        format!("\nHints -{}", score.hints)
+    } else {
+        String::new()
+    };
+
+    format!(
+        "\n\nRewards {}\nCombos +{}\nTime bonus +{}\nEfficiency +{}\nBacktracking -{}{}\nScore {}",
+        score.rewards,
+        score.combos,
+        score.time_bonus,
+        score.efficiency,
+        score.backtracking,
+        hints,
+        score.total
+    )
+}
+
+impl<'a> Scene<'a> for Ended {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        shared.play_music(ctx, MENU_MUSIC)?;
+
+        Ok(match self.menu.take_chosen() {
+            Some(Action::NextLevel) => match self.mode {
+                Mode::Campaign(level) => super::play(ctx, shared, Mode::Campaign(level + 1))?,
+                Mode::Free => Transition::None,
+            },
+            Some(Action::Retry) => {
+                let game = self.initial.clone();
+                Transition::Switch(Box::new(Playing::new(ctx, shared, game, self.mode)?))
+            }
+            Some(Action::PlayAgain) => super::play(ctx, shared, self.mode)?,
+            Some(Action::WatchReplay) => match shared.replay.clone() {
+                Some(replay) => Transition::Switch(Box::new(Replaying::new(shared, replay)?)),
+                None => Transition::None,
+            },
+            Some(Action::Title) => Transition::Switch(Box::new(Title::new(shared, None))),
+            Some(Action::Quit) => Transition::Quit,
+            None => Transition::None,
+        })
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        self.menu.key_down(keycode);
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        super::draw_menu_screen(ctx, shared, &self.title, &self.menu, true)
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
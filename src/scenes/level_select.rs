@@ -0,0 +1,67 @@
+use ggez::graphics::Text;
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use super::{Mode, Scene, Shared, Title, Transition, MENU_MUSIC};
+use crate::menu::Menu;
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Play(usize),
+    Back,
+}
+
+/// Lists the campaign levels unlocked so far
+pub struct LevelSelect {
+    title: Text,
+    menu: Menu<Action>,
+    back: bool,
+}
+
+impl LevelSelect {
+    pub fn new(shared: &Shared) -> LevelSelect {
+        let current = shared.progress.current(&shared.campaign);
+        let mut items: Vec<_> = shared.campaign.levels[..=current]
+            .iter()
+            .enumerate()
+            .map(|(i, level)| (format!("{}. {}", i + 1, level.name), Action::Play(i)))
+            .collect();
+        items.push(("Back".to_owned(), Action::Back));
+
+        let mut menu = Menu::new(&items);
+        menu.select(current);
+
+        LevelSelect {
+            title: super::title_text("Levels"),
+            menu,
+            back: false,
+        }
+    }
+}
+
+impl<'a> Scene<'a> for LevelSelect {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        shared.play_music(ctx, MENU_MUSIC)?;
+
+        if self.back {
+            return Ok(Transition::Switch(Box::new(Title::new(shared, None))));
+        }
+
+        match self.menu.take_chosen() {
+            Some(Action::Play(level)) => super::play(ctx, shared, Mode::Campaign(level)),
+            Some(Action::Back) => Ok(Transition::Switch(Box::new(Title::new(shared, None)))),
+            None => Ok(Transition::None),
+        }
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Escape => self.back = true,
+            _ => self.menu.key_down(keycode),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        super::draw_menu_screen(ctx, shared, &self.title, &self.menu, false)
+    }
+}
@@ -0,0 +1,425 @@
+//! Screens of the game, kept on a stack: the top scene gets the input and the updates, while
+//! overlays such as the pause menu let the scenes below show through.
+
+use ggez::audio::{SoundData, SoundSource, Source};
+use ggez::event::{self, Axis, Button, EventHandler, GamepadId};
+use ggez::graphics::{
+    self, Color, DrawMode, DrawParam, Image, Mesh, Rect, Scale, Text, TextFragment,
+};
+use ggez::input::keyboard::{KeyCode, KeyMods};
+use ggez::input::mouse::{self, MouseButton};
+use ggez::nalgebra::{Point2, Vector2};
+use ggez::{timer, Context, GameResult};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::Assets;
+use crate::campaign::{Campaign, Progress};
+use crate::controls::Controls;
+use crate::formats::Format;
+use crate::game::{Game, Rules, TICKS_PER_SECOND};
+use crate::gamepad::{self, Gamepads};
+use crate::ghost::Ghosts;
+use crate::hud::BACKGROUND_COLOR;
+use crate::layout::Layout;
+use crate::leaderboard::Leaderboard;
+use crate::maze::Maze;
+use crate::menu::Menu;
+use crate::options::Options;
+use crate::replay::Replay;
+use crate::save::SaveGame;
+use crate::tmx;
+
+mod controls;
+mod ended;
+mod level_select;
+mod notice;
+mod paused;
+mod playing;
+mod replaying;
+mod scores;
+mod title;
+mod view;
+
+pub use controls::ControlsMenu;
+pub use ended::Ended;
+pub use level_select::LevelSelect;
+pub use notice::Notice;
+pub use paused::Paused;
+pub use playing::Playing;
+pub use replaying::Replaying;
+pub use scores::Scores;
+pub use title::Title;
+
+pub const MENU_MUSIC: &str = "/audio/menu/audio_loop.ogg";
+pub const GAME_MUSIC: &str = "/audio/game/audio_loop.ogg";
+/// Seeds tried in a row before giving up on making a new maze
+const SEED_ATTEMPTS: u64 = 8;
+
+/// What the scene stack does after an update
+pub enum Transition<'a> {
+    None,
+    /// Puts a scene on top of the current one
+    Push(Box<dyn Scene<'a> + 'a>),
+    /// Goes back to the scene below
+    Pop,
+    /// Replaces the whole stack with a scene
+    Switch(Box<dyn Scene<'a> + 'a>),
+    Quit,
+}
+
+pub trait Scene<'a> {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>>;
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult;
+
+    /// Input is only handled in `update`, this records it
+    fn key_down(&mut self, _ctx: &mut Context, _keycode: KeyCode) {}
+
+    /// Records a click at `point`, in screen coordinates, like `key_down`
+    fn mouse_button_down(&mut self, _ctx: &mut Context, _button: MouseButton, _point: Point2<f32>) {
+    }
+
+    /// Image drawn instead of the system cursor, if any
+    fn cursor(&self, _ctx: &mut Context) -> Option<&'static str> {
+        None
+    }
+
+    /// A gamepad button was pressed, menus get the key it stands for
+    fn button_down(&mut self, ctx: &mut Context, button: Button) {
+        if let Some(keycode) = gamepad::menu_key(button) {
+            self.key_down(ctx, keycode);
+        }
+    }
+
+    /// The window went to the background, or a gamepad was unplugged
+    fn focus_lost(&mut self) {}
+
+    /// The game is about to close
+    fn quit(&mut self, _ctx: &mut Context) {}
+
+    /// Whether the scenes below are drawn too
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// What a game is part of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    /// A maze made from the command line options
+    Free,
+    /// A level of the campaign, by index
+    Campaign(usize),
+}
+
+/// What every scene may use
+pub struct Shared<'a> {
+    pub images: &'a Assets<Image>,
+    pub sounds: &'a Assets<SoundData>,
+    pub options: Options,
+    pub layout: Layout,
+    pub campaign: Campaign,
+    pub progress: Progress,
+    pub leaderboard: Leaderboard,
+    /// Game left when the window was last closed, until it is resumed
+    pub save: Option<SaveGame>,
+    /// The last game played, to watch it again
+    pub replay: Option<Replay>,
+    pub ghosts: Ghosts,
+    pub controls: Controls,
+    pub gamepads: Gamepads,
+    music: Option<(&'static str, Source)>,
+}
+
+impl<'a> Shared<'a> {
+    pub fn new(
+        images: &'a Assets<Image>,
+        sounds: &'a Assets<SoundData>,
+        options: Options,
+        layout: Layout,
+        campaign: Campaign,
+        progress: Progress,
+        leaderboard: Leaderboard,
+    ) -> Shared<'a> {
+        Shared {
+            images,
+            sounds,
+            options,
+            layout,
+            campaign,
+            progress,
+            leaderboard,
+            save: None,
+            replay: None,
+            ghosts: Ghosts::default(),
+            controls: Controls::default(),
+            gamepads: Gamepads::default(),
+            music: None,
+        }
+    }
+
+    /// Loops `track`, unless it is already playing
+    pub fn play_music(&mut self, ctx: &mut Context, track: &'static str) -> GameResult {
+        if let Some((current, _)) = &self.music {
+            if *current == track {
+                return Ok(());
+            }
+        }
+
+        if let Some((_, mut source)) = self.music.take() {
+            source.stop();
+        }
+        let mut source = Source::from_data(ctx, self.sounds[track].clone())?;
+        source.set_repeat(true);
+        source.play()?;
+        self.music = Some((track, source));
+
+        Ok(())
+    }
+
+    /// Name of the leaderboard table of `mode`
+    pub fn table_name(&self, mode: Mode) -> &str {
+        match mode {
+            Mode::Free => "Free play",
+            Mode::Campaign(level) => &self.campaign.levels[level].name,
+        }
+    }
+
+    /// A new game in `mode`, mazes being generated from a random seed, or the next ones when no
+    /// maze can be made from it
+    pub fn new_game(&self, mode: Mode) -> GameResult<Game> {
+        let first = rand::thread_rng().gen::<u32>() as u64;
+        let game_from = |seed| match mode {
+            Mode::Free => new_game(&self.options, seed),
+            Mode::Campaign(level) => self.campaign.levels[level].new_game(seed),
+        };
+
+        (1..SEED_ATTEMPTS).fold(game_from(first), |result, i| {
+            result.or_else(|_| game_from(first + i))
+        })
+    }
+}
+
+/// Starts playing a new game in `mode`, or tells why there is none over the current menu
+pub fn play<'a>(ctx: &mut Context, shared: &Shared<'a>, mode: Mode) -> GameResult<Transition<'a>> {
+    let game = match shared.new_game(mode) {
+        Ok(game) => game,
+        Err(e) => {
+            let notice = Notice::new("No maze", &e.to_string());
+            return Ok(Transition::Push(Box::new(notice)));
+        }
+    };
+    Ok(Transition::Switch(Box::new(Playing::new(
+        ctx, shared, game, mode,
+    )?)))
+}
+
+/// Loads the level given in `options`, or generates a maze from `seed`
+pub fn new_game(options: &Options, seed: u64) -> GameResult<Game> {
+    let (maze, mut rules) = match &options.level {
+        // Tiled maps also hold rules, and may point to tilesets next to them
+        Some(level) if Format::from_path(level)? == Format::Tmx => tmx::load(level)?,
+        Some(level) => {
+            let maze = Maze::import(&std::fs::read_to_string(level)?, Format::from_path(level)?)?;
+            let rules = Rules::for_maze(&maze);
+            (maze, rules)
+        }
+        None => {
+            let generator = options.algorithm.generator();
+            let maze = Maze::from_seed(options.size, seed, &*generator)?;
+            (maze, Rules::default())
+        }
+    };
+    if let Some(enemies) = options.enemies {
+        rules.enemies = enemies;
+    }
+
+    Ok(Game::new(maze, rules))
+}
+
+/// Draws a title above a menu, in the middle of the window. Overlays get a panel over the
+/// darkened scenes below.
+pub fn draw_menu_screen<A: Copy>(
+    ctx: &mut Context,
+    shared: &Shared,
+    title: &Text,
+    menu: &Menu<A>,
+    overlay: bool,
+) -> GameResult {
+    const TITLE_HEIGHT: f32 = 70.;
+    const PANEL_WIDTH: f32 = 260.;
+
+    let (w, h) = shared.layout.window;
+    let (title_w, title_h) = title.dimensions(ctx);
+    // titles may hold a few more lines, such as the score
+    let title_height = TITLE_HEIGHT.max(title_h as f32 + 20.);
+    let max_menu_height = h - title_height - 20.;
+    let height = title_height + menu.height(max_menu_height);
+    let top = ((h - height) / 2.).max(0.);
+
+    if overlay {
+        let shade = Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., w, h),
+            Color::new(0., 0., 0., 0.6),
+        )?;
+        graphics::draw(ctx, &shade, DrawParam::new())?;
+        graphics::draw(
+            ctx,
+            &shared.images["/ui/panel_brown.png"],
+            DrawParam::new()
+                .dest(Point2::new((w - PANEL_WIDTH) / 2., top - 10.))
+                .scale(Vector2::new(PANEL_WIDTH / 100., (height + 20.) / 100.)),
+        )?;
+    }
+
+    graphics::draw(
+        ctx,
+        title,
+        DrawParam::new().dest(Point2::new(
+            (w - title_w as f32) / 2.,
+            top + (title_height - title_h as f32) / 2.,
+        )),
+    )?;
+    menu.draw(
+        ctx,
+        shared.images,
+        w / 2.,
+        top + title_height,
+        max_menu_height,
+    )
+}
+
+/// A big title, as drawn by `draw_menu_screen`
+pub fn title_text(title: &str) -> Text {
+    Text::new(TextFragment::new(title).scale(Scale::uniform(36.)))
+}
+
+pub struct SceneStack<'a> {
+    scenes: Vec<Box<dyn Scene<'a> + 'a>>,
+    shared: Shared<'a>,
+}
+
+impl<'a> SceneStack<'a> {
+    pub fn new(first: Box<dyn Scene<'a> + 'a>, shared: Shared<'a>) -> SceneStack<'a> {
+        SceneStack {
+            scenes: vec![first],
+            shared,
+        }
+    }
+}
+
+impl SceneStack<'_> {
+    /// Lets every scene know that the game closes, the top one first
+    fn quit(&mut self, ctx: &mut Context) {
+        for scene in self.scenes.iter_mut().rev() {
+            scene.quit(ctx);
+        }
+    }
+}
+
+impl EventHandler for SceneStack<'_> {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let scene = match self.scenes.last_mut() {
+            Some(scene) => scene,
+            None => {
+                event::quit(ctx);
+                return Ok(());
+            }
+        };
+
+        if self.shared.gamepads.remove_disconnected(ctx) {
+            scene.focus_lost();
+        }
+        let transition = scene.update(ctx, &mut self.shared)?;
+        // fixed steps are only for the scene that used them, not a backlog to catch up on later
+        while timer::check_update_time(ctx, TICKS_PER_SECOND) {}
+
+        match transition {
+            Transition::None => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Switch(scene) => self.scenes = vec![scene],
+            Transition::Quit => {
+                self.quit(ctx);
+                event::quit(ctx)
+            }
+        }
+
+        Ok(())
+    }
+
+    fn quit_event(&mut self, ctx: &mut Context) -> bool {
+        self.quit(ctx);
+        false
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, _: KeyMods, _: bool) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.key_down(ctx, keycode);
+        }
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.mouse_button_down(ctx, button, Point2::new(x, y));
+        }
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, button: Button, id: GamepadId) {
+        self.shared.gamepads.button_down(id, button);
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.button_down(ctx, button);
+        }
+    }
+
+    fn gamepad_button_up_event(&mut self, _ctx: &mut Context, button: Button, id: GamepadId) {
+        self.shared.gamepads.button_up(id, button);
+    }
+
+    fn gamepad_axis_event(&mut self, ctx: &mut Context, axis: Axis, value: f32, id: GamepadId) {
+        // a stick pushed somewhere browses menus like the d-pad
+        let button = self.shared.gamepads.axis(id, axis, value);
+        if let (Some(button), Some(scene)) = (button, self.scenes.last_mut()) {
+            scene.button_down(ctx, button);
+        }
+    }
+
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) {
+        if !gained {
+            if let Some(scene) = self.scenes.last_mut() {
+                scene.focus_lost();
+            }
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        graphics::clear(ctx, BACKGROUND_COLOR.into());
+
+        let bottom = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in &mut self.scenes[bottom..] {
+            scene.draw(ctx, &self.shared)?;
+        }
+
+        let cursor = self.scenes.last().and_then(|scene| scene.cursor(ctx));
+        mouse::set_cursor_hidden(ctx, cursor.is_some());
+        if let Some(cursor) = cursor {
+            let dest = mouse::position(ctx);
+            graphics::draw(
+                ctx,
+                &self.shared.images[cursor],
+                DrawParam::new().dest(dest),
+            )?;
+        }
+
+        graphics::present(ctx)
+    }
+}
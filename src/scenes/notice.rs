@@ -0,0 +1,54 @@
+use ggez::graphics::{Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use super::{Scene, Shared, Transition};
+use crate::menu::Menu;
+
+/// Tells why something could not be done, over the menu it was chosen from
+pub struct Notice {
+    title: Text,
+    menu: Menu<()>,
+    back: bool,
+}
+
+impl Notice {
+    pub fn new(title: &str, message: &str) -> Notice {
+        let mut title = super::title_text(title);
+        title.add(TextFragment::new(format!("\n\n{}", message)).scale(Scale::uniform(14.)));
+
+        Notice {
+            title,
+            menu: Menu::new(&[("Back", ())]),
+            back: false,
+        }
+    }
+}
+
+impl<'a> Scene<'a> for Notice {
+    fn update(
+        &mut self,
+        _ctx: &mut Context,
+        _shared: &mut Shared<'a>,
+    ) -> GameResult<Transition<'a>> {
+        if self.back || self.menu.take_chosen().is_some() {
+            return Ok(Transition::Pop);
+        }
+        Ok(Transition::None)
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Escape => self.back = true,
+            _ => self.menu.key_down(keycode),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        super::draw_menu_screen(ctx, shared, &self.title, &self.menu, true)
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
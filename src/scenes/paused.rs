@@ -0,0 +1,86 @@
+use ggez::graphics::Text;
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use super::{Mode, Playing, Scene, Shared, Title, Transition};
+use crate::controls::{self, Controls};
+use crate::game::Game;
+use crate::menu::Menu;
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Resume,
+    Restart,
+    NewMaze,
+    Title,
+    Quit,
+}
+
+/// Drawn over the game, whose clock stands still until it is resumed
+pub struct Paused {
+    title: Text,
+    menu: Menu<Action>,
+    /// The paused game as it was when it started
+    initial: Game,
+    mode: Mode,
+    /// To resume with the pause key too
+    controls: Controls,
+    resume: bool,
+}
+
+impl Paused {
+    pub fn new(shared: &Shared, initial: Game, mode: Mode) -> Paused {
+        Paused {
+            title: super::title_text("Pause"),
+            menu: Menu::new(&[
+                ("Resume", Action::Resume),
+                ("Restart", Action::Restart),
+                ("New maze", Action::NewMaze),
+                ("Title", Action::Title),
+                ("Quit", Action::Quit),
+            ]),
+            initial,
+            mode,
+            controls: shared.controls.clone(),
+            resume: false,
+        }
+    }
+}
+
+impl<'a> Scene<'a> for Paused {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        if self.resume {
+            return Ok(Transition::Pop);
+        }
+
+        Ok(match self.menu.take_chosen() {
+            Some(Action::Resume) => Transition::Pop,
+            Some(Action::Restart) => {
+                let game = self.initial.clone();
+                Transition::Switch(Box::new(Playing::new(ctx, shared, game, self.mode)?))
+            }
+            Some(Action::NewMaze) => super::play(ctx, shared, self.mode)?,
+            Some(Action::Title) => Transition::Switch(Box::new(Title::new(shared, None))),
+            Some(Action::Quit) => Transition::Quit,
+            None => Transition::None,
+        })
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        if keycode == KeyCode::Escape
+            || self.controls.action(keycode) == Some(controls::Action::Pause)
+        {
+            self.resume = true;
+        } else {
+            self.menu.key_down(keycode);
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        super::draw_menu_screen(ctx, shared, &self.title, &self.menu, true)
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,348 @@
+use ggez::audio::{SoundSource, Source};
+use ggez::event::Button;
+use ggez::input::keyboard::KeyCode;
+use ggez::input::mouse::{self, MouseButton};
+use ggez::nalgebra::Point2;
+use ggez::timer;
+use ggez::{Context, GameResult};
+
+use super::view::GameView;
+use super::{Ended, Mode, Paused, Scene, Shared, Transition, GAME_MUSIC};
+use crate::controls::{Action, Controls};
+use crate::game::{Command, Event, Game, Outcome, TICKS_PER_SECOND};
+use crate::gamepad;
+use crate::ghost::Ghost;
+use crate::input::MoveInput;
+use crate::leaderboard::Entry;
+use crate::maze::CellIndex;
+use crate::pathfinding;
+use crate::player::Player;
+use crate::replay::Replay;
+use crate::save::SaveGame;
+
+/// Cursor over a cell the player can be sent to
+const WALK_CURSOR: &str = "/ui/cursorHand_beige.png";
+/// Cursor over a wall or a cell never seen
+const BLOCKED_CURSOR: &str = "/ui/cursorSword_silver.png";
+
+/// Turns keys, buttons and clicks into commands, ticks the `Game` and draws it
+pub struct Playing<'a> {
+    game: Game,
+    /// The game as it was when it started, to restart it
+    initial: Game,
+    mode: Mode,
+    view: GameView<'a>,
+    /// Commands given so far, when the game is recorded from its start
+    replay: Option<Replay>,
+    /// The best game won on this maze, to race it
+    ghost: Option<Ghost>,
+    show_ghost: bool,
+    controls: Controls,
+    moves: MoveInput,
+    restart: bool,
+    /// Asked for a hint since the last tick
+    hint_requested: bool,
+    /// Clicked since the last update, in screen coordinates
+    click: Option<Point2<f32>>,
+    /// Cells left to walk through after a click, the next one last
+    walk: Vec<CellIndex>,
+    running_sound: Source,
+    death_sound: Source,
+    paused: bool,
+}
+
+impl<'a> Playing<'a> {
+    pub fn new(
+        ctx: &mut Context,
+        shared: &Shared<'a>,
+        game: Game,
+        mode: Mode,
+    ) -> GameResult<Playing<'a>> {
+        let mut running_sound =
+            Source::from_data(ctx, shared.sounds["/audio/game/running.ogg"].clone())?;
+        running_sound.set_volume(0.3);
+
+        let mut death_sound =
+            Source::from_data(ctx, shared.sounds["/audio/game/death.ogg"].clone())?;
+        death_sound.set_volume(2.0);
+
+        let mut ghost = shared.ghosts.best(&game).cloned().and_then(Ghost::new);
+        if let Some(ghost) = &mut ghost {
+            ghost.catch_up(game.tick_count());
+        }
+
+        Ok(Playing {
+            view: GameView::new(shared, &game),
+            replay: Replay::new(&game),
+            ghost,
+            show_ghost: true,
+            controls: shared.controls.clone(),
+            moves: MoveInput::default(),
+            restart: false,
+            hint_requested: false,
+            click: None,
+            walk: vec![],
+            initial: game.clone(),
+            mode,
+            game,
+            running_sound,
+            death_sound,
+            paused: false,
+        })
+    }
+
+    /// Goes on with a saved game, paused until the player is ready
+    pub fn resume(
+        ctx: &mut Context,
+        shared: &Shared<'a>,
+        save: SaveGame,
+    ) -> GameResult<Playing<'a>> {
+        let mut playing = Playing::new(ctx, shared, save.game, save.mode)?;
+        playing.initial = save.initial;
+        playing.paused = true;
+        Ok(playing)
+    }
+
+    /// Keeps up with the directions held on the keyboard and on gamepads
+    fn read_moves(&mut self, ctx: &Context, shared: &Shared) {
+        let controls = &self.controls;
+        self.moves.hold(|dir| {
+            controls.is_pressed(ctx, Action::moving(dir))
+                || shared.gamepads.direction() == Some(dir)
+        });
+    }
+
+    fn act(&mut self, action: Action) {
+        match action {
+            Action::Pause => self.paused = true,
+            Action::Restart => self.restart = true,
+            Action::ToggleGhost => self.show_ghost = !self.show_ghost,
+            Action::Hint => self.hint_requested = true,
+            _ => match action.direction() {
+                Some(dir) => self.moves.press(dir),
+                None => self.view.act(action),
+            },
+        }
+    }
+
+    /// Whether a click on `cell` sends the player there: it must be ground seen under the fog
+    fn is_walkable(&self, cell: CellIndex) -> bool {
+        let maze = self.game.maze();
+        self.game.is_hidden()
+            && !maze.get(cell).is_wall()
+            && self.game.vision().is_explored(maze, cell)
+            && !matches!(maze.get_reward(cell), Some(r) if r.malus && r.found)
+    }
+
+    /// Plans the way to `target` through the cells already seen, from where the player is going
+    fn walk_to(&mut self, target: CellIndex) {
+        let player = self.game.player();
+        let from = player.destination();
+        let path = pathfinding::find_path(self.game.maze(), from, target, |cell| {
+            self.is_walkable(cell)
+        });
+
+        self.walk = match path {
+            Some(path) => {
+                let moving = player.is_moving().then_some(from);
+                moving.into_iter().chain(path).rev().collect()
+            }
+            None => vec![],
+        };
+    }
+
+    /// The next step of the walk, which is over once the player strays from it
+    fn walk_command(&mut self) -> Option<Command> {
+        let player = self.game.player();
+        if self.walk.last() == Some(&player.cell()) && !player.is_moving() {
+            self.walk.pop();
+        }
+
+        let next = *self.walk.last()?;
+        match pathfinding::direction_to(self.game.maze(), player.cell(), next) {
+            Some(dir) => Some(Command::Move(dir)),
+            None => {
+                self.walk.clear();
+                None
+            }
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared<'a>,
+        event: Event,
+    ) -> GameResult<Transition<'a>> {
+        match event {
+            Event::Stepped => {
+                if self.game.player().step_count() % 2 == 1 {
+                    self.running_sound.play()?;
+                }
+            }
+            Event::MalusFound | Event::EnemyHit => self.death_sound.play_detached()?,
+            Event::Ended(outcome) => {
+                if let (Outcome::Won, Mode::Campaign(level)) = (outcome, self.mode) {
+                    shared.progress.clear(level);
+                    if let Err(e) = shared.progress.save(ctx) {
+                        eprintln!("could not save the progress: {}", e);
+                    }
+                }
+
+                let mut rank = None;
+                if outcome == Outcome::Won {
+                    let entry = Entry::new(&shared.options.name, &self.game);
+                    let table = shared.table_name(self.mode).to_owned();
+                    rank = shared.leaderboard.add(&table, entry);
+                    if let Err(e) = shared.leaderboard.save(ctx) {
+                        eprintln!("could not save the leaderboard: {}", e);
+                    }
+                }
+
+                if let Some(replay) = &mut self.replay {
+                    replay.finish(outcome);
+                    if let Err(e) = replay.save(ctx) {
+                        eprintln!("could not save the replay: {}", e);
+                    }
+                    if shared.ghosts.offer(replay) {
+                        if let Err(e) = shared.ghosts.save(ctx) {
+                            eprintln!("could not save the ghosts: {}", e);
+                        }
+                    }
+                }
+                // a resumed game was not recorded, there is nothing to watch
+                shared.replay = self.replay.take();
+
+                let score = self.game.score().breakdown();
+                let initial = self.initial.clone();
+                let ended = Ended::new(shared, outcome, initial, self.mode, score, rank);
+                return Ok(Transition::Push(Box::new(ended)));
+            }
+            Event::FogFell | Event::RewardFound | Event::HintShown => {}
+        }
+        Ok(Transition::None)
+    }
+}
+
+/// The player of `ghost`, unless hidden or done racing
+fn visible_ghost(ghost: Option<&Ghost>, shown: bool) -> Option<&Player> {
+    ghost
+        .filter(|ghost| shown && !ghost.has_finished())
+        .map(Ghost::player)
+}
+
+impl<'a> Scene<'a> for Playing<'a> {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        shared.play_music(ctx, GAME_MUSIC)?;
+
+        if self.paused {
+            self.paused = false;
+            let paused = Paused::new(shared, self.initial.clone(), self.mode);
+            return Ok(Transition::Push(Box::new(paused)));
+        }
+
+        if self.restart {
+            let game = self.initial.clone();
+            let playing = Playing::new(ctx, shared, game, self.mode)?;
+            return Ok(Transition::Switch(Box::new(playing)));
+        }
+
+        self.read_moves(ctx, shared);
+        if self.moves.is_active() {
+            self.walk.clear();
+        }
+        if let Some(click) = self.click.take() {
+            match self.view.cell_at(&self.game, click) {
+                Some(cell) if self.is_walkable(cell) => {
+                    self.moves.cancel();
+                    self.walk_to(cell);
+                }
+                _ => {}
+            }
+        }
+
+        while timer::check_update_time(ctx, TICKS_PER_SECOND) {
+            // a hint takes the place of a move for one tick
+            let command = if std::mem::take(&mut self.hint_requested) {
+                Some(Command::Hint)
+            } else {
+                match self.moves.next(&self.game) {
+                    Some(dir) => Some(Command::Move(dir)),
+                    None => self.walk_command(),
+                }
+            };
+            if let Some(replay) = &mut self.replay {
+                replay.record(self.game.tick_count(), command);
+            }
+            let events = self.game.tick(command);
+            if let Some(ghost) = &mut self.ghost {
+                ghost.tick();
+            }
+            for event in events {
+                let transition = self.handle_event(ctx, shared, event)?;
+                if !matches!(transition, Transition::None) {
+                    return Ok(transition);
+                }
+            }
+        }
+
+        let ghost = visible_ghost(self.ghost.as_ref(), self.show_ghost);
+        self.view
+            .update(&self.game, ghost, timer::delta(ctx).as_secs_f32());
+
+        Ok(Transition::None)
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        if let Some(action) = self.controls.action(keycode) {
+            self.act(action);
+        }
+    }
+
+    fn button_down(&mut self, _ctx: &mut Context, button: Button) {
+        if let Some(dir) = gamepad::dpad_direction(button) {
+            self.moves.press(dir);
+        } else if let Some(action) = gamepad::action(button) {
+            self.act(action);
+        }
+    }
+
+    fn mouse_button_down(&mut self, _ctx: &mut Context, button: MouseButton, point: Point2<f32>) {
+        if button == MouseButton::Left {
+            self.click = Some(point);
+        }
+    }
+
+    fn cursor(&self, ctx: &mut Context) -> Option<&'static str> {
+        if !self.game.is_hidden() {
+            return None;
+        }
+
+        let cell = self.view.cell_at(&self.game, mouse::position(ctx).into())?;
+        if self.is_walkable(cell) {
+            Some(WALK_CURSOR)
+        } else {
+            Some(BLOCKED_CURSOR)
+        }
+    }
+
+    fn focus_lost(&mut self) {
+        self.paused = true;
+    }
+
+    fn quit(&mut self, ctx: &mut Context) {
+        if self.game.outcome().is_some() {
+            return;
+        }
+
+        let save = SaveGame::new(self.mode, self.initial.clone(), self.game.clone());
+        if let Err(e) = save.save(ctx) {
+            eprintln!("could not save the game: {}", e);
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        let ghost = visible_ghost(self.ghost.as_ref(), self.show_ghost);
+        self.view.draw(ctx, shared, &self.game, ghost)
+    }
+}
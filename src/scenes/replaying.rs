@@ -0,0 +1,144 @@
+use ggez::graphics::{self, DrawParam, Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::nalgebra::Vector2;
+use ggez::timer;
+use ggez::{Context, GameResult};
+
+use super::view::GameView;
+use super::{Scene, Shared, Title, Transition, GAME_MUSIC};
+use crate::controls::Controls;
+use crate::game::{Game, TICKS_PER_SECOND};
+use crate::replay::Replay;
+
+/// Fast-forward speeds, in ticks per fixed step
+const SPEEDS: [u32; 4] = [1, 2, 4, 8];
+/// How far Left and Right seek, in ticks
+const SEEK_TICKS: u64 = 5 * TICKS_PER_SECOND as u64;
+
+/// Plays a recorded game again, with pause, fast-forward and seeking
+pub struct Replaying<'a> {
+    replay: Replay,
+    /// The game as it was when the recording started, to seek backwards
+    initial: Game,
+    game: Game,
+    view: GameView<'a>,
+    paused: bool,
+    /// Index in `SPEEDS`
+    speed: usize,
+    /// Tick to jump to
+    seek: Option<u64>,
+    /// Only used for the view, the replay controls are fixed
+    controls: Controls,
+    back: bool,
+}
+
+impl<'a> Replaying<'a> {
+    pub fn new(shared: &Shared<'a>, replay: Replay) -> GameResult<Replaying<'a>> {
+        let initial = replay.game()?;
+
+        Ok(Replaying {
+            view: GameView::new(shared, &initial),
+            game: initial.clone(),
+            initial,
+            replay,
+            paused: false,
+            speed: 0,
+            seek: None,
+            controls: shared.controls.clone(),
+            back: false,
+        })
+    }
+
+    /// Advances the game by one recorded tick, returns whether there was one left
+    fn step(&mut self) -> bool {
+        if self.game.outcome().is_some() || self.game.tick_count() >= self.replay.length() {
+            return false;
+        }
+
+        self.game
+            .tick(self.replay.command_at(self.game.tick_count()));
+        true
+    }
+
+    /// Replays the game up to `tick`, from the start when it is in the past
+    fn seek(&mut self, tick: u64) {
+        if tick < self.game.tick_count() {
+            self.game = self.initial.clone();
+        }
+        while self.game.tick_count() < tick && self.step() {}
+    }
+
+    fn status(&self) -> Text {
+        let secs = |ticks: u64| ticks / TICKS_PER_SECOND as u64;
+        let state = if self.paused { "paused" } else { "playing" };
+
+        Text::new(
+            TextFragment::new(format!(
+                "replay x{}\n{}\n{}s / {}s\n\nspace pause\nF speed\nleft/right seek\nhome start\nesc quit",
+                SPEEDS[self.speed],
+                state,
+                secs(self.game.tick_count()),
+                secs(self.replay.length()),
+            ))
+            .scale(Scale::uniform(14.)),
+        )
+    }
+}
+
+impl<'a> Scene<'a> for Replaying<'a> {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        shared.play_music(ctx, GAME_MUSIC)?;
+
+        if self.back {
+            return Ok(Transition::Switch(Box::new(Title::new(shared, None))));
+        }
+
+        if let Some(tick) = self.seek.take() {
+            self.seek(tick);
+        }
+        while timer::check_update_time(ctx, TICKS_PER_SECOND) {
+            if !self.paused {
+                for _ in 0..SPEEDS[self.speed] {
+                    self.step();
+                }
+            }
+        }
+
+        self.view
+            .update(&self.game, None, timer::delta(ctx).as_secs_f32());
+        Ok(Transition::None)
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        let tick = self.seek.unwrap_or_else(|| self.game.tick_count());
+
+        match keycode {
+            KeyCode::Escape => self.back = true,
+            KeyCode::Space => self.paused = !self.paused,
+            KeyCode::F => self.speed = (self.speed + 1) % SPEEDS.len(),
+            KeyCode::Left => self.seek = Some(tick.saturating_sub(SEEK_TICKS)),
+            KeyCode::Right => self.seek = Some((tick + SEEK_TICKS).min(self.replay.length())),
+            KeyCode::Home => self.seek = Some(0),
+            _ => {
+                if let Some(action) = self.controls.action(keycode) {
+                    self.view.act(action);
+                }
+            }
+        }
+    }
+
+    fn focus_lost(&mut self) {
+        self.paused = true;
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        self.view.draw(ctx, shared, &self.game, None)?;
+
+        graphics::draw(
+            ctx,
+            &self.status(),
+            DrawParam::new()
+                .dest(shared.layout.panel + Vector2::new(12., shared.layout.window.1 - 150.)),
+        )
+    }
+}
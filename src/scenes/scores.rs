@@ -0,0 +1,109 @@
+use ggez::graphics::{Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+
+use super::{Mode, Scene, Shared, Title, Transition, MENU_MUSIC};
+use crate::leaderboard::TABLE_SIZE;
+use crate::menu::Menu;
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Back,
+}
+
+/// Top games of each mode, browsed with the left and right arrows
+pub struct Scores {
+    /// Free play, then the unlocked campaign levels
+    modes: Vec<Mode>,
+    shown: usize,
+    /// Which mode `title` holds the table of
+    title_mode: usize,
+    title: Text,
+    menu: Menu<Action>,
+    back: bool,
+}
+
+impl Scores {
+    pub fn new(shared: &Shared) -> Scores {
+        let current = shared.progress.current(&shared.campaign);
+        let modes: Vec<_> = std::iter::once(Mode::Free)
+            .chain((0..=current).map(Mode::Campaign))
+            .collect();
+
+        Scores {
+            title: table_text(shared, modes[0]),
+            modes,
+            shown: 0,
+            title_mode: 0,
+            menu: Menu::new(&[("Back", Action::Back)]),
+            back: false,
+        }
+    }
+}
+
+/// The title and the table of `mode`
+fn table_text(shared: &Shared, mode: Mode) -> Text {
+    let name = shared.table_name(mode);
+    let entries = shared.leaderboard.table(name);
+
+    let mut lines = format!("\n< {} >\n\n", name);
+    for (rank, entry) in entries.iter().enumerate() {
+        lines += &format!(
+            "{:>2}. {}  {}  {:.1}s  {} steps  {}x{}  seed {}  {}\n",
+            rank + 1,
+            entry.name,
+            entry.score,
+            entry.time,
+            entry.steps,
+            entry.size.0,
+            entry.size.1,
+            entry.seed.map_or("-".to_owned(), |seed| seed.to_string()),
+            entry.day(),
+        );
+    }
+    if entries.is_empty() {
+        lines += "no game won yet\n";
+    }
+    lines += &"\n".repeat(TABLE_SIZE.saturating_sub(entries.len().max(1)));
+
+    let mut text = super::title_text("Scores");
+    text.add(TextFragment::new(lines).scale(Scale::uniform(14.)));
+    text
+}
+
+impl<'a> Scene<'a> for Scores {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        shared.play_music(ctx, MENU_MUSIC)?;
+
+        if self.back {
+            return Ok(Transition::Switch(Box::new(Title::new(shared, None))));
+        }
+        if self.title_mode != self.shown {
+            self.title = table_text(shared, self.modes[self.shown]);
+            self.title_mode = self.shown;
+        }
+
+        match self.menu.take_chosen() {
+            Some(Action::Back) => Ok(Transition::Switch(Box::new(Title::new(shared, None)))),
+            None => Ok(Transition::None),
+        }
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Escape => self.back = true,
+            KeyCode::Left | KeyCode::Right => {
+                let count = self.modes.len();
+                self.shown = match keycode {
+                    KeyCode::Left => (self.shown + count - 1) % count,
+                    _ => (self.shown + 1) % count,
+                };
+            }
+            _ => self.menu.key_down(keycode),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        super::draw_menu_screen(ctx, shared, &self.title, &self.menu, false)
+    }
+}
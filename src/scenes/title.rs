@@ -0,0 +1,122 @@
+use ggez::graphics::{self, DrawParam, Scale, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::nalgebra::Point2;
+use ggez::{Context, GameResult};
+
+use super::{
+    ControlsMenu, LevelSelect, Mode, Playing, Scene, Scores, Shared, Transition, MENU_MUSIC,
+};
+use crate::game::Game;
+use crate::menu::Menu;
+use crate::save::SaveGame;
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Resume,
+    Campaign,
+    Levels,
+    FreePlay,
+    Scores,
+    Controls,
+    Quit,
+}
+
+/// First screen, the game starts from there
+pub struct Title {
+    title: Text,
+    hint: Text,
+    menu: Menu<Action>,
+    /// Free play game to start with, a new one is made when there is none
+    game: Option<Game>,
+    quit: bool,
+}
+
+impl Title {
+    pub fn new(shared: &Shared, game: Option<Game>) -> Title {
+        let mut items = vec![
+            ("Campaign", Action::Campaign),
+            ("Free play", Action::FreePlay),
+            ("Scores", Action::Scores),
+            ("Controls", Action::Controls),
+            ("Quit", Action::Quit),
+        ];
+        if shared.progress.cleared > 0 {
+            items[0].0 = "Continue";
+            items.insert(1, ("Levels", Action::Levels));
+        }
+        if shared.save.is_some() {
+            items.insert(0, ("Resume", Action::Resume));
+        }
+
+        Title {
+            title: super::title_text("Paté 2 Crabe"),
+            hint: Text::new(
+                TextFragment::new("arrows to choose, enter to confirm").scale(Scale::uniform(14.)),
+            ),
+            menu: Menu::new(&items),
+            game,
+            quit: false,
+        }
+    }
+}
+
+impl<'a> Scene<'a> for Title {
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared<'a>) -> GameResult<Transition<'a>> {
+        shared.play_music(ctx, MENU_MUSIC)?;
+
+        if self.quit {
+            return Ok(Transition::Quit);
+        }
+
+        match self.menu.take_chosen() {
+            Some(Action::Resume) => match shared.save.take() {
+                Some(save) => {
+                    if let Err(e) = SaveGame::delete(ctx) {
+                        eprintln!("could not remove the saved game: {}", e);
+                    }
+                    let playing = Playing::resume(ctx, shared, save)?;
+                    Ok(Transition::Switch(Box::new(playing)))
+                }
+                None => Ok(Transition::None),
+            },
+            Some(Action::Campaign) => {
+                let level = shared.progress.current(&shared.campaign);
+                super::play(ctx, shared, Mode::Campaign(level))
+            }
+            Some(Action::Levels) => Ok(Transition::Switch(Box::new(LevelSelect::new(shared)))),
+            Some(Action::FreePlay) => match self.game.take() {
+                Some(game) => {
+                    let playing = Playing::new(ctx, shared, game, Mode::Free)?;
+                    Ok(Transition::Switch(Box::new(playing)))
+                }
+                None => super::play(ctx, shared, Mode::Free),
+            },
+            Some(Action::Scores) => Ok(Transition::Switch(Box::new(Scores::new(shared)))),
+            Some(Action::Controls) => Ok(Transition::Switch(Box::new(ControlsMenu::new(shared)))),
+            Some(Action::Quit) => Ok(Transition::Quit),
+            None => Ok(Transition::None),
+        }
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Escape => self.quit = true,
+            _ => self.menu.key_down(keycode),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &Shared<'a>) -> GameResult {
+        super::draw_menu_screen(ctx, shared, &self.title, &self.menu, false)?;
+
+        let (w, h) = shared.layout.window;
+        let (hint_w, hint_h) = self.hint.dimensions(ctx);
+        graphics::draw(
+            ctx,
+            &self.hint,
+            DrawParam::new().dest(Point2::new(
+                (w - hint_w as f32) / 2.,
+                h - hint_h as f32 - 10.,
+            )),
+        )
+    }
+}
@@ -0,0 +1,134 @@
+use ggez::graphics::Color;
+use ggez::nalgebra::Point2;
+use ggez::{Context, GameResult};
+
+use super::Shared;
+use crate::camera::{Camera, MAX_ZOOM, MIN_ZOOM};
+use crate::controls::Action;
+use crate::game::Game;
+use crate::hud::Hud;
+use crate::maze::CellIndex;
+use crate::minimap::Minimap;
+use crate::player::Player;
+use crate::render::{self, EnemyRenderer, MazeRenderer, PlayerRenderer};
+
+/// How opaque ghosts are
+const GHOST_ALPHA: f32 = 0.4;
+
+/// Draws a `Game` through a camera following the player, next to the side panel. A ghost player
+/// may be drawn too, over the fog.
+pub struct GameView<'a> {
+    maze_renderer: MazeRenderer,
+    player_renderer: PlayerRenderer<'a>,
+    enemy_renderer: EnemyRenderer<'a>,
+    ghost_renderer: PlayerRenderer<'a>,
+    hud: Hud,
+    minimap: Minimap,
+    camera: Camera,
+    /// Zoom chosen by the player, used once the fog fell
+    zoom: f32,
+}
+
+impl<'a> GameView<'a> {
+    pub fn new(shared: &Shared<'a>, game: &Game) -> GameView<'a> {
+        let zoom = shared.options.zoom;
+        let mut camera = Camera::new(shared.layout.maze, game.maze().dim(), zoom);
+        camera.set_zoom(camera.fit_zoom());
+        camera.center_on(game.player().pos());
+
+        GameView {
+            maze_renderer: MazeRenderer::new(game.maze(), shared.images),
+            player_renderer: PlayerRenderer::new(shared.images),
+            enemy_renderer: EnemyRenderer::new(shared.images),
+            ghost_renderer: PlayerRenderer::new(shared.images),
+            hud: Hud::new(game),
+            minimap: Minimap::default(),
+            camera,
+            zoom,
+        }
+    }
+
+    /// Zooms in or out, or switches the minimap to the full map
+    pub fn act(&mut self, action: Action) {
+        match action {
+            Action::ToggleMinimap => self.minimap.toggle(),
+            Action::ZoomIn => self.zoom *= 1.25,
+            Action::ZoomOut => self.zoom /= 1.25,
+            _ => {}
+        }
+        self.zoom = self.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// The cell of `game` under a point on screen, if any
+    pub fn cell_at(&self, game: &Game, point: Point2<f32>) -> Option<CellIndex> {
+        if !self.camera.viewport().contains(point) {
+            return None;
+        }
+
+        let (x, y) = self.camera.screen_to_cell(point);
+        let cell = CellIndex::from([x.floor() as usize, y.floor() as usize]);
+        Some(cell).filter(|cell| x >= 0. && y >= 0. && game.maze().is_in_range(*cell))
+    }
+
+    /// Catches up with `game` and its `ghost`, `dt` seconds after the last update
+    pub fn update(&mut self, game: &Game, ghost: Option<&Player>, dt: f32) {
+        self.hud.update(game);
+
+        // the whole maze is shown while it has to be memorised
+        let zoom = if game.is_hidden() {
+            self.zoom
+        } else {
+            self.camera.fit_zoom()
+        };
+        self.camera.set_zoom(zoom);
+        self.camera.follow(game.player().pos(), dt);
+        self.player_renderer
+            .update(game.player(), game.tick_count());
+        self.enemy_renderer.update(game.tick_count());
+        if let Some(ghost) = ghost {
+            self.ghost_renderer.update(ghost, game.tick_count());
+        }
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        shared: &Shared<'a>,
+        game: &Game,
+        ghost: Option<&Player>,
+    ) -> GameResult {
+        let param = self.camera.draw_param();
+
+        self.maze_renderer.draw(
+            ctx,
+            game.maze(),
+            game.is_hidden(),
+            self.camera.visible_cells(),
+            param,
+        )?;
+        // under the fog, enemies are only seen where the player looks
+        let vision = game.vision();
+        let enemies = game.enemies().iter().filter(|enemy| {
+            !game.is_hidden()
+                || enemy
+                    .cells()
+                    .any(|cell| vision.is_visible(game.maze(), cell))
+        });
+        self.enemy_renderer.draw(ctx, enemies, param)?;
+        self.player_renderer.draw(ctx, game.player(), param)?;
+
+        if game.is_hidden() {
+            render::draw_fog(ctx, &self.camera, game.maze(), game.vision())?;
+        }
+        if let Some(hint) = game.hint() {
+            render::draw_hint(ctx, &self.camera, hint)?;
+        }
+        if let Some(ghost) = ghost {
+            let tint = Color::new(1., 1., 1., GHOST_ALPHA);
+            self.ghost_renderer.draw(ctx, ghost, param.color(tint))?;
+        }
+
+        self.hud.draw(ctx, shared.images, &shared.layout)?;
+        self.minimap.draw(ctx, game, shared.layout.minimap)
+    }
+}
@@ -0,0 +1,269 @@
+//! How many points a game is worth. Rewards and combos count as soon as they are found, while the
+//! time bonus and the efficiency bonus are only granted to a won game. Walking again on a cell
+//! already walked on costs points, and so does asking for a hint.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Rules, TICKS_PER_SECOND};
+use crate::maze::{CellIndex, Maze};
+use crate::solver;
+
+/// Points for each reward found
+pub const REWARD_POINTS: u32 = 100;
+/// Extra points for a reward found shortly after another one, times the length of the combo
+pub const COMBO_POINTS: u32 = 50;
+/// Longest time between two rewards of a combo, in ticks
+pub const COMBO_TICKS: u64 = 5 * TICKS_PER_SECOND as u64;
+/// Time bonus when the exit is reached as soon as the fog fell
+pub const TIME_BONUS: u32 = 1000;
+/// Time bonus lost for each second spent under the fog
+pub const TIME_BONUS_DECAY: u32 = 10;
+/// Efficiency bonus when walking exactly the shortest route
+pub const EFFICIENCY_POINTS: u32 = 500;
+/// Points lost for each step onto a cell already walked on
+pub const BACKTRACK_PENALTY: u32 = 10;
+/// Points lost for each hint
+pub const HINT_PENALTY: u32 = 150;
+
+/// The points of a game, rule by rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Breakdown {
+    pub rewards: u32,
+    pub combos: u32,
+    pub time_bonus: u32,
+    pub efficiency: u32,
+    /// Subtracted from the other ones
+    pub backtracking: u32,
+    /// Subtracted from the other ones
+    pub hints: u32,
+    pub total: u32,
+}
+
+/// Keeps track of what the score is made of during a game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    /// Length of the shortest route from the start to the exit picking enough rewards
+    par: Option<usize>,
+    /// Cells walked on, in row-major order
+    visited: Vec<bool>,
+    steps: usize,
+    backtracks: usize,
+    #[serde(default)]
+    hints: u32,
+    rewards: u32,
+    combo: u32,
+    best_combo: u32,
+    combo_points: u32,
+    last_reward: Option<u64>,
+    /// Ticks spent under the fog, once the game is won
+    won_after: Option<u64>,
+}
+
+impl Score {
+    pub fn new(maze: &Maze, rules: &Rules) -> Score {
+        let (w, h) = maze.dim();
+        let mut visited = vec![false; w * h];
+        visited[maze.index(maze.start())] = true;
+
+        Score {
+            par: solver::route_length(maze, maze.start(), rules.reward_quota as usize),
+            visited,
+            steps: 0,
+            backtracks: 0,
+            hints: 0,
+            rewards: 0,
+            combo: 0,
+            best_combo: 0,
+            combo_points: 0,
+            last_reward: None,
+            won_after: None,
+        }
+    }
+
+    /// The player started walking to `to`
+    pub fn stepped(&mut self, maze: &Maze, to: CellIndex) {
+        let visited = &mut self.visited[maze.index(to)];
+
+        self.steps += 1;
+        if *visited {
+            self.backtracks += 1;
+        }
+        *visited = true;
+    }
+
+    /// A reward was found at `tick`
+    pub fn reward_found(&mut self, tick: u64) {
+        self.combo = match self.last_reward {
+            Some(last) if tick - last <= COMBO_TICKS => self.combo + 1,
+            _ => 1,
+        };
+        self.rewards += 1;
+        self.combo_points += COMBO_POINTS * (self.combo - 1);
+        self.best_combo = self.best_combo.max(self.combo);
+        self.last_reward = Some(tick);
+    }
+
+    /// The player was shown the way
+    pub fn hint_used(&mut self) {
+        self.hints += 1;
+    }
+
+    /// The exit was reached after `ticks` under the fog
+    pub fn won(&mut self, ticks: u64) {
+        self.won_after = Some(ticks);
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Steps taken onto cells already walked on
+    pub fn backtracks(&self) -> usize {
+        self.backtracks
+    }
+
+    /// Number of hints asked for
+    pub fn hints(&self) -> u32 {
+        self.hints
+    }
+
+    /// Most rewards found in a row, each shortly after the previous one
+    pub fn best_combo(&self) -> u32 {
+        self.best_combo
+    }
+
+    /// Length of the shortest winning route, if there is one
+    pub fn par(&self) -> Option<usize> {
+        self.par
+    }
+
+    pub fn breakdown(&self) -> Breakdown {
+        let (time_bonus, efficiency) = match self.won_after {
+            Some(ticks) => {
+                let secs = (ticks / TICKS_PER_SECOND as u64).min(u32::MAX as u64) as u32;
+                let efficiency = match self.par {
+                    Some(par) if self.steps > 0 => {
+                        EFFICIENCY_POINTS * par.min(self.steps) as u32 / self.steps as u32
+                    }
+                    _ => EFFICIENCY_POINTS,
+                };
+                (
+                    TIME_BONUS.saturating_sub(TIME_BONUS_DECAY.saturating_mul(secs)),
+                    efficiency,
+                )
+            }
+            None => (0, 0),
+        };
+
+        let rewards = REWARD_POINTS * self.rewards;
+        let backtracking = BACKTRACK_PENALTY * self.backtracks as u32;
+        let hints = HINT_PENALTY * self.hints;
+
+        Breakdown {
+            rewards,
+            combos: self.combo_points,
+            time_bonus,
+            efficiency,
+            backtracking,
+            hints,
+            total: (rewards + self.combo_points + time_bonus + efficiency)
+                .saturating_sub(backtracking + hints),
+        }
+    }
+
+    /// Whether the score was made for a maze as big as `maze`
+    pub fn fits(&self, maze: &Maze) -> bool {
+        let (w, h) = maze.dim();
+        self.visited.len() == w * h
+    }
+
+    pub fn total(&self) -> u32 {
+        self.breakdown().total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile::Tile;
+
+    /// A corridor from the start at (1, 1) to the exit at (6, 1)
+    fn corridor() -> (Maze, Rules) {
+        let mut maze = Maze::new((7, 3));
+        for x in 1..7 {
+            maze.set([x, 1].into(), Tile::Ground);
+        }
+        let rules = Rules {
+            reward_quota: 0,
+            ..Rules::default()
+        };
+        (maze, rules)
+    }
+
+    #[test]
+    fn rewards_and_combos() {
+        let (maze, rules) = corridor();
+        let mut score = Score::new(&maze, &rules);
+        score.reward_found(0);
+        score.reward_found(COMBO_TICKS);
+        score.reward_found(3 * COMBO_TICKS);
+
+        assert_eq!(score.best_combo(), 2);
+        assert_eq!(
+            score.breakdown(),
+            Breakdown {
+                rewards: 3 * REWARD_POINTS,
+                combos: COMBO_POINTS,
+                total: 3 * REWARD_POINTS + COMBO_POINTS,
+                ..Breakdown::default()
+            }
+        );
+    }
+
+    #[test]
+    fn won_game() {
+        let (maze, rules) = corridor();
+        let mut score = Score::new(&maze, &rules);
+        assert_eq!(score.par(), Some(5));
+
+        for x in &[2, 3, 2, 3, 4, 5, 6] {
+            score.stepped(&maze, [*x, 1].into());
+        }
+        score.hint_used();
+        score.won(30 * TICKS_PER_SECOND as u64);
+
+        assert_eq!((score.steps(), score.backtracks()), (7, 2));
+        let time_bonus = TIME_BONUS - 30 * TIME_BONUS_DECAY;
+        let efficiency = EFFICIENCY_POINTS * 5 / 7;
+        assert_eq!(
+            score.breakdown(),
+            Breakdown {
+                time_bonus,
+                efficiency,
+                backtracking: 2 * BACKTRACK_PENALTY,
+                hints: HINT_PENALTY,
+                total: time_bonus + efficiency - 2 * BACKTRACK_PENALTY - HINT_PENALTY,
+                ..Breakdown::default()
+            }
+        );
+    }
+
+    #[test]
+    fn penalties_never_go_below_zero() {
+        let (maze, rules) = corridor();
+        let mut score = Score::new(&maze, &rules);
+        score.reward_found(0);
+        for _ in 0..3 {
+            score.hint_used();
+        }
+        assert_eq!(score.total(), 0);
+    }
+
+    #[test]
+    fn slow_wins_lose_the_time_bonus() {
+        let (maze, rules) = corridor();
+        let mut score = Score::new(&maze, &rules);
+        score.won(3600 * TICKS_PER_SECOND as u64);
+        assert_eq!(score.breakdown().time_bonus, 0);
+    }
+}
@@ -0,0 +1,249 @@
+//! Routes through the maze: the shortest way from a cell to the exit picking enough apples on the
+//! way, without stepping on a malus. Used for the par of the score and for the hints.
+
+use crate::maze::{CellIndex, Maze};
+use crate::pathfinding;
+
+/// Above this many apples, the route goes to the nearest apple each time instead of trying every
+/// order
+const MAX_EXACT_APPLES: usize = 12;
+
+/// Length of the shortest route from `from` to the exit through `quota` apples not found yet
+pub fn route_length(maze: &Maze, from: CellIndex, quota: usize) -> Option<usize> {
+    plan(maze, from, quota).map(|(length, _)| length)
+}
+
+/// Cells of the shortest route from `from` to the exit through `quota` apples not found yet,
+/// `from` excluded
+pub fn route(maze: &Maze, from: CellIndex, quota: usize) -> Option<Vec<CellIndex>> {
+    let (length, waypoints) = plan(maze, from, quota)?;
+    let mut path = Vec::with_capacity(length);
+    let mut current = from;
+
+    for waypoint in waypoints {
+        path.extend(pathfinding::bfs_path(maze, current, waypoint, |pos| {
+            maze.is_safe(pos)
+        })?);
+        current = waypoint;
+    }
+    Some(path)
+}
+
+/// Length of the shortest route and the apples it picks then the exit, in order
+fn plan(maze: &Maze, from: CellIndex, quota: usize) -> Option<(usize, Vec<CellIndex>)> {
+    let apples: Vec<_> = maze
+        .rewards()
+        .iter()
+        .filter(|r| !r.malus && !r.found)
+        .map(|r| *r.pos())
+        .collect();
+    let quota = quota.min(apples.len());
+
+    // distances from `from`, then from every apple, to the apples and the exit
+    let distances: Vec<Vec<Option<usize>>> = std::iter::once(from)
+        .chain(apples.iter().copied())
+        .map(|from| {
+            let all = maze.distances(from, |pos| maze.is_safe(pos));
+            apples
+                .iter()
+                .chain(std::iter::once(&maze.exit()))
+                .map(|to| all[maze.index(*to)])
+                .collect()
+        })
+        .collect();
+    let exit = apples.len();
+
+    let (length, order) = if quota == 0 {
+        (distances[0][exit]?, vec![])
+    } else if apples.len() > MAX_EXACT_APPLES {
+        greedy_order(&distances, quota)?
+    } else {
+        exact_order(&distances, quota)?
+    };
+
+    let waypoints = order
+        .into_iter()
+        .map(|i| apples[i])
+        .chain(std::iter::once(maze.exit()))
+        .collect();
+    Some((length, waypoints))
+}
+
+/// The best order to pick `quota` apples in, `distances` being as built by `plan`
+fn exact_order(distances: &[Vec<Option<usize>>], quota: usize) -> Option<(usize, Vec<usize>)> {
+    let exit = distances.len() - 1;
+
+    // best[set][last] is the shortest route from the start through the apples in `set`, ending
+    // on `last`, and the apple picked before `last`
+    let mut best = vec![vec![None::<(usize, Option<usize>)>; exit]; 1 << exit];
+    for (i, d) in distances[0][..exit].iter().enumerate() {
+        best[1 << i][i] = d.map(|d| (d, None));
+    }
+
+    let mut shortest: Option<(usize, usize, usize)> = None;
+    for set in 1..best.len() {
+        for last in 0..exit {
+            let length = match best[set][last] {
+                Some((length, _)) => length,
+                None => continue,
+            };
+
+            if (set as u32).count_ones() as usize == quota {
+                if let Some(to_exit) = distances[last + 1][exit] {
+                    if shortest.is_none_or(|(s, _, _)| length + to_exit < s) {
+                        shortest = Some((length + to_exit, set, last));
+                    }
+                }
+                continue;
+            }
+            for next in (0..exit).filter(|next| set & (1 << next) == 0) {
+                if let Some(d) = distances[last + 1][next] {
+                    let entry = &mut best[set | 1 << next][next];
+                    if entry.is_none_or(|(e, _)| length + d < e) {
+                        *entry = Some((length + d, Some(last)));
+                    }
+                }
+            }
+        }
+    }
+
+    let (length, mut set, last) = shortest?;
+    let mut order = vec![];
+    let mut last = Some(last);
+    while let Some(apple) = last {
+        order.push(apple);
+        last = best[set][apple].and_then(|(_, previous)| previous);
+        set &= !(1 << apple);
+    }
+    order.reverse();
+    Some((length, order))
+}
+
+/// The order going to the nearest apple not picked yet each time, `distances` being as built by
+/// `plan`
+fn greedy_order(distances: &[Vec<Option<usize>>], quota: usize) -> Option<(usize, Vec<usize>)> {
+    let exit = distances.len() - 1;
+    let mut order = Vec::with_capacity(quota);
+    let (mut from, mut length) = (0, 0);
+
+    for _ in 0..quota {
+        let (next, d) = (0..exit)
+            .filter(|i| !order.contains(i))
+            .filter_map(|i| distances[from][i].map(|d| (i, d)))
+            .min_by_key(|(_, d)| *d)?;
+        order.push(next);
+        length += d;
+        from = next + 1;
+    }
+
+    Some((length + distances[from][exit]?, order))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::generator::Algorithm;
+    use crate::maze::{MazeRng, Population, DIRECTIONS};
+    use crate::tile::Tile;
+
+    /// Small mazes, some walls being knocked down so that there is more than one way around
+    fn mazes() -> Vec<Maze> {
+        let population = Population {
+            rewards: 5,
+            maluses: 2,
+        };
+        let mut mazes = vec![];
+        for (seed, algorithm) in Algorithm::ALL.iter().enumerate() {
+            let seed = seed as u64;
+            let generator = algorithm.generator();
+            let mut maze = Maze::from_seed_with((11, 11), seed, &*generator, population).unwrap();
+            mazes.push(maze.clone());
+
+            let mut rng = MazeRng::seed_from_u64(seed);
+            for _ in 0..8 {
+                let (x, y) = (rng.gen_range(1, 10), rng.gen_range(1, 10));
+                if (x + y) % 2 == 1 {
+                    maze.set([x, y].into(), Tile::Ground);
+                }
+            }
+            mazes.push(maze);
+        }
+        mazes
+    }
+
+    /// Length of the shortest route found by trying every way, picking apples when walked on
+    fn brute_force(maze: &Maze, quota: usize) -> Option<usize> {
+        let apples: Vec<_> = maze
+            .rewards()
+            .iter()
+            .filter(|r| !r.malus)
+            .map(|r| *r.pos())
+            .collect();
+        let quota = quota.min(apples.len());
+        let states = 1 << apples.len();
+        let mut distances = vec![None; maze.dim().0 * maze.dim().1 * states];
+        let state = |pos: CellIndex, set: usize| maze.index(pos) * states + set;
+
+        let mut queue = VecDeque::new();
+        distances[state(maze.start(), 0)] = Some(0);
+        queue.push_back((maze.start(), 0usize, 0));
+
+        while let Some((pos, set, distance)) = queue.pop_front() {
+            if pos == maze.exit() && set.count_ones() as usize >= quota {
+                return Some(distance);
+            }
+            for dir in &DIRECTIONS {
+                let next = match maze.neighbour(pos, *dir) {
+                    Some(next) if !maze.get(next).is_wall() && maze.is_safe(next) => next,
+                    _ => continue,
+                };
+                let set = apples
+                    .iter()
+                    .position(|apple| *apple == next)
+                    .map_or(set, |i| set | 1 << i);
+                if distances[state(next, set)].is_none() {
+                    distances[state(next, set)] = Some(distance + 1);
+                    queue.push_back((next, set, distance + 1));
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn routes_are_the_shortest() {
+        for maze in mazes() {
+            for quota in 0..=5 {
+                let length = route_length(&maze, maze.start(), quota);
+                assert_eq!(length, brute_force(&maze, quota), "quota {}", quota);
+            }
+        }
+    }
+
+    #[test]
+    fn routes_can_be_walked() {
+        for maze in mazes() {
+            let route = route(&maze, maze.start(), 3).unwrap();
+            assert_eq!(Some(route.len()), route_length(&maze, maze.start(), 3));
+            assert_eq!(route.last(), Some(&maze.exit()));
+
+            let mut from = maze.start();
+            for &cell in &route {
+                assert!(pathfinding::direction_to(&maze, from, cell).is_some());
+                assert!(!maze.get(cell).is_wall() && maze.is_safe(cell));
+                from = cell;
+            }
+
+            let apples = maze
+                .rewards()
+                .iter()
+                .filter(|r| !r.malus && route.contains(r.pos()))
+                .count();
+            assert!(apples >= 3);
+        }
+    }
+}
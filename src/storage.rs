@@ -0,0 +1,79 @@
+//! Files kept in the user directory. Each one starts with the version of its layout, bumped
+//! whenever the layout changes. A file that cannot be read, is corrupted or was written by another
+//! version is never lost: it is copied next to itself with `.bak` appended, and the game goes on
+//! as if there was none.
+
+use std::io::{Read, Write};
+
+use ggez::{filesystem, Context};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+/// Only the version, read first to tell old files from corrupted ones
+#[derive(Deserialize)]
+struct Header {
+    /// Files written before they had a version read as 0
+    #[serde(default)]
+    version: u32,
+}
+
+/// Parses `text`, which must have been written with the layout `version`
+pub fn parse<T: DeserializeOwned>(text: &str, version: u32) -> Result<T, String> {
+    let header: Header = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if header.version != version {
+        return Err(format!("unsupported version {}", header.version));
+    }
+    serde_json::from_str(text).map_err(|e| e.to_string())
+}
+
+/// Reads `what` from `path` in the user directory, `check` telling whether it can be used. Gives
+/// `None` when there is no such file or it was set aside.
+pub fn load_checked<T, F>(
+    ctx: &mut Context,
+    path: &str,
+    version: u32,
+    what: &str,
+    check: F,
+) -> Option<T>
+where
+    T: DeserializeOwned,
+    F: FnOnce(&T) -> Result<(), String>,
+{
+    let mut file = filesystem::open(ctx, path).ok()?;
+    let mut text = String::new();
+    let loaded = file
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())
+        .and_then(|_| parse(&text, version))
+        .and_then(|value| check(&value).map(|_| value));
+
+    match loaded {
+        Ok(value) => Some(value),
+        Err(reason) => {
+            set_aside(ctx, path, &text, what, &reason);
+            None
+        }
+    }
+}
+
+/// Reads `what` from `path` in the user directory, see `load_checked`
+pub fn load<T: DeserializeOwned>(
+    ctx: &mut Context,
+    path: &str,
+    version: u32,
+    what: &str,
+) -> Option<T> {
+    load_checked(ctx, path, version, what, |_| Ok(()))
+}
+
+/// Keeps `text`, read from `path`, in the backup file
+fn set_aside(ctx: &mut Context, path: &str, text: &str, what: &str, reason: &str) {
+    let backup = format!("{}.bak", path);
+    eprintln!("ignoring {} ({}), it is kept in {}", what, reason, backup);
+
+    if let Err(e) =
+        filesystem::create(ctx, &backup).and_then(|mut file| Ok(file.write_all(text.as_bytes())?))
+    {
+        eprintln!("could not back up {}: {}", what, e);
+    }
+}
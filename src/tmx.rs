@@ -0,0 +1,348 @@
+//! Hand-authored levels made with the [Tiled](https://www.mapeditor.org) editor.
+//!
+//! The first tile layer is the maze: empty cells are ground, tiles are walls unless their type is
+//! `ground` or their `wall` bool property is false. Objects of type (or name) `start`, `exit` and
+//! `reward` place the player start, the exit and the rewards, a reward with a true `malus` bool
+//! property being a malus. The map properties `preview` and `time_limit`, in seconds,
+//! `reward_quota`, `health`, `enemies` and `view_radius`, in cells, tune the rules. A `seed`
+//! string property records where a generated maze came from, and a true `found` bool property
+//! marks a reward as already found.
+
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::path::Path;
+
+use tiled::{Map, Object, Properties, PropertyValue, TiledError};
+
+use crate::game::{Rules, TICKS_PER_SECOND};
+use crate::layout::TILE_SIZE;
+use crate::maze::{CellIndex, Maze, MazeError};
+use crate::rewards::Reward;
+use crate::tile::Tile;
+
+#[derive(Debug)]
+pub enum TmxError {
+    Tiled(TiledError),
+    Maze(MazeError),
+    /// The map parsed but does not describe a playable level
+    Invalid(String),
+}
+
+impl fmt::Display for TmxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TmxError::Tiled(e) => write!(f, "invalid tmx file: {}", e),
+            TmxError::Maze(e) => write!(f, "invalid level: {}", e),
+            TmxError::Invalid(reason) => write!(f, "invalid level: {}", reason),
+        }
+    }
+}
+
+impl Error for TmxError {}
+
+impl From<TiledError> for TmxError {
+    fn from(e: TiledError) -> Self {
+        TmxError::Tiled(e)
+    }
+}
+
+impl From<MazeError> for TmxError {
+    fn from(e: MazeError) -> Self {
+        TmxError::Maze(e)
+    }
+}
+
+/// Loads a level, `path` being a path on the regular filesystem
+pub fn load(path: &Path) -> Result<(Maze, Rules), TmxError> {
+    from_map(&tiled::parse_file(path)?)
+}
+
+pub fn from_map(map: &Map) -> Result<(Maze, Rules), TmxError> {
+    let invalid = |reason: &str| TmxError::Invalid(reason.to_owned());
+
+    let dim = (map.width as usize, map.height as usize);
+    if dim.0 < 3 || dim.1 < 3 {
+        return Err(invalid("the map is smaller than 3x3"));
+    }
+    let layer = map.layers.first().ok_or_else(|| invalid("no tile layer"))?;
+
+    let mut maze = Maze::new(dim);
+    for (y, row) in layer.tiles.iter().enumerate().take(dim.1) {
+        for (x, tile) in row.iter().enumerate().take(dim.0) {
+            maze.set([x, y].into(), tile_of(map, tile.gid));
+        }
+    }
+
+    let (mut start, mut exit) = (None, None);
+    for object in map.object_groups.iter().flat_map(|group| &group.objects) {
+        let kind = if object.obj_type.is_empty() {
+            &object.name
+        } else {
+            &object.obj_type
+        };
+
+        let pos = match kind.as_str() {
+            "start" | "exit" | "reward" => cell_of(map, &maze, object)?,
+            _ => continue,
+        };
+        if maze.get(pos).is_wall() {
+            return Err(TmxError::Invalid(format!(
+                "{} at ({}, {}) is on a wall",
+                kind, pos.x, pos.y
+            )));
+        }
+
+        match kind.as_str() {
+            "start" => start = Some(pos),
+            "exit" => exit = Some(pos),
+            _ => {
+                let flag = |name| {
+                    matches!(
+                        object.properties.get(name),
+                        Some(PropertyValue::BoolValue(true))
+                    )
+                };
+                let mut reward = Reward::new(pos, flag("malus"));
+                reward.found = flag("found");
+                maze.add_reward(reward);
+            }
+        }
+    }
+
+    match map.properties.get("seed") {
+        None => {}
+        Some(PropertyValue::StringValue(seed)) if seed.parse::<u64>().is_ok() => {
+            maze.set_seed(seed.parse().ok())
+        }
+        Some(_) => return Err(invalid("seed must be a string holding a number")),
+    }
+
+    maze.set_start(start.ok_or_else(|| invalid("no start object"))?);
+    maze.set_exit(exit.ok_or_else(|| invalid("no exit object"))?);
+    maze.validate()?;
+
+    let apples = maze.rewards().iter().filter(|r| !r.malus).count();
+    let mut rules = Rules::for_maze(&maze);
+
+    if let Some(secs) = seconds(&map.properties, "preview")? {
+        // the fog falls on the first tick at the earliest
+        rules.preview_ticks = secs.max(1);
+    }
+    rules.time_limit = seconds(&map.properties, "time_limit")?;
+    match map.properties.get("health") {
+        None => {}
+        Some(PropertyValue::IntValue(health)) if (1..=u8::MAX as i32).contains(health) => {
+            rules.health = *health as u8
+        }
+        Some(_) => return Err(invalid("health must be a positive int")),
+    }
+    match map.properties.get("enemies") {
+        None => {}
+        Some(PropertyValue::IntValue(enemies)) if (0..=u8::MAX as i32).contains(enemies) => {
+            rules.enemies = *enemies as u8
+        }
+        Some(_) => return Err(invalid("enemies must be a non-negative int")),
+    }
+    match map.properties.get("view_radius") {
+        None => {}
+        Some(PropertyValue::IntValue(radius)) if (1..=u8::MAX as i32).contains(radius) => {
+            rules.view_radius = *radius as u8
+        }
+        Some(_) => return Err(invalid("view_radius must be a positive int")),
+    }
+    if let Some(quota) = map.properties.get("reward_quota") {
+        match quota {
+            PropertyValue::IntValue(quota) if (0..=apples as i32).contains(quota) => {
+                rules.reward_quota = *quota as u8
+            }
+            _ => {
+                return Err(invalid(
+                    "reward_quota must be an int up to the number of rewards",
+                ))
+            }
+        }
+    }
+
+    Ok((maze, rules))
+}
+
+/// Writes a maze as a map `from_map` reads back identically. Its tileset points to the game
+/// images as seen from `assets/levels`.
+pub fn write(maze: &Maze) -> String {
+    let mut tmx = String::new();
+    write_map(maze, &mut tmx).expect("writing to a String never fails");
+    tmx
+}
+
+fn write_map(maze: &Maze, tmx: &mut String) -> fmt::Result {
+    const GROUND: u32 = 1;
+    const WALL: u32 = 2;
+
+    let (w, h) = maze.dim();
+    let size = TILE_SIZE as usize;
+
+    writeln!(tmx, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        tmx,
+        r#"<map version="1.2" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{size}" tileheight="{size}" infinite="0" nextlayerid="3" nextobjectid="{}">"#,
+        w,
+        h,
+        maze.rewards().len() + 3,
+        size = size,
+    )?;
+    if let Some(seed) = maze.seed() {
+        writeln!(tmx, " <properties>")?;
+        writeln!(tmx, r#"  <property name="seed" value="{}"/>"#, seed)?;
+        writeln!(tmx, " </properties>")?;
+    }
+
+    writeln!(
+        tmx,
+        r#" <tileset firstgid="{}" name="maze" tilewidth="{size}" tileheight="{size}" tilecount="2" columns="0">"#,
+        GROUND,
+        size = size,
+    )?;
+    for (gid, kind, image) in &[(GROUND, "ground", "grass"), (WALL, "wall", "wall_crss_all")] {
+        writeln!(tmx, r#"  <tile id="{}" type="{}">"#, gid - GROUND, kind)?;
+        writeln!(
+            tmx,
+            r#"   <image width="{size}" height="{size}" source="../game/{}.png"/>"#,
+            image,
+            size = size,
+        )?;
+        writeln!(tmx, "  </tile>")?;
+    }
+    writeln!(tmx, " </tileset>")?;
+
+    writeln!(
+        tmx,
+        r#" <layer id="1" name="maze" width="{}" height="{}">"#,
+        w, h
+    )?;
+    writeln!(tmx, r#"  <data encoding="csv">"#)?;
+    let rows: Vec<String> = (0..h)
+        .map(|y| {
+            let gids: Vec<_> = (0..w)
+                .map(|x| match maze.get([x, y].into()) {
+                    Tile::Wall => WALL.to_string(),
+                    Tile::Ground => GROUND.to_string(),
+                })
+                .collect();
+            gids.join(",")
+        })
+        .collect();
+    writeln!(tmx, "{}", rows.join(",\n"))?;
+    writeln!(tmx, "  </data>")?;
+    writeln!(tmx, " </layer>")?;
+
+    writeln!(tmx, r#" <objectgroup id="2" name="objects">"#)?;
+    let objects = [
+        ("start", "start", maze.start()),
+        ("exit", "exit", maze.exit()),
+    ];
+    for (id, (name, kind, pos)) in objects.iter().enumerate() {
+        writeln!(tmx, "{}/>", object_tag(id + 1, name, kind, *pos))?;
+    }
+    for (id, reward) in maze.rewards().iter().enumerate() {
+        let name = if reward.malus { "crab" } else { "apple" };
+        writeln!(
+            tmx,
+            "{}>",
+            object_tag(id + objects.len() + 1, name, "reward", *reward.pos())
+        )?;
+        writeln!(tmx, "   <properties>")?;
+        writeln!(
+            tmx,
+            r#"    <property name="found" type="bool" value="{}"/>"#,
+            reward.found
+        )?;
+        writeln!(
+            tmx,
+            r#"    <property name="malus" type="bool" value="{}"/>"#,
+            reward.malus
+        )?;
+        writeln!(tmx, "   </properties>")?;
+        writeln!(tmx, "  </object>")?;
+    }
+    writeln!(tmx, " </objectgroup>")?;
+    writeln!(tmx, "</map>")
+}
+
+/// An unclosed object tag covering the cell at `pos`
+fn object_tag(id: usize, name: &str, kind: &str, pos: CellIndex) -> String {
+    let size = TILE_SIZE as usize;
+
+    format!(
+        r#"  <object id="{}" name="{}" type="{}" x="{}" y="{}" width="{size}" height="{size}""#,
+        id,
+        name,
+        kind,
+        pos.x * size,
+        pos.y * size,
+        size = size,
+    )
+}
+
+/// Whether a global tile id is a wall, tiles without a type nor a `wall` property are walls
+fn tile_of(map: &Map, gid: u32) -> Tile {
+    if gid == 0 {
+        return Tile::Ground;
+    }
+
+    let tile = map.get_tileset_by_gid(gid).and_then(|tileset| {
+        tileset
+            .tiles
+            .iter()
+            .find(|tile| tile.id == gid - tileset.first_gid)
+    });
+
+    let wall = tile.and_then(|tile| match tile.tile_type.as_deref() {
+        Some("wall") => Some(true),
+        Some("ground") => Some(false),
+        _ => match tile.properties.get("wall") {
+            Some(PropertyValue::BoolValue(wall)) => Some(*wall),
+            _ => None,
+        },
+    });
+
+    match wall {
+        Some(false) => Tile::Ground,
+        _ => Tile::Wall,
+    }
+}
+
+/// The cell under the center of an object
+fn cell_of(map: &Map, maze: &Maze, object: &Object) -> Result<CellIndex, TmxError> {
+    // tile objects are anchored at their bottom-left corner, others at their top-left one
+    let top = if object.gid != 0 {
+        object.y - object.height
+    } else {
+        object.y
+    };
+    let x = (object.x + object.width / 2.) / map.tile_width as f32;
+    let y = (top + object.height / 2.) / map.tile_height as f32;
+
+    let pos = CellIndex::from([x.max(0.) as usize, y.max(0.) as usize]);
+    if x < 0. || y < 0. || !maze.is_in_range(pos) {
+        return Err(TmxError::Invalid(format!(
+            "object {} is outside the map",
+            object.id
+        )));
+    }
+    Ok(pos)
+}
+
+/// Reads a duration property given in seconds, as ticks
+fn seconds(properties: &Properties, name: &str) -> Result<Option<u64>, TmxError> {
+    let secs = match properties.get(name) {
+        None => return Ok(None),
+        Some(PropertyValue::IntValue(secs)) => *secs as f32,
+        Some(PropertyValue::FloatValue(secs)) => *secs,
+        Some(_) => return Err(TmxError::Invalid(format!("{} must be a number", name))),
+    };
+
+    if secs < 0. {
+        return Err(TmxError::Invalid(format!("{} must not be negative", name)));
+    }
+    Ok(Some((secs * TICKS_PER_SECOND as f32).round() as u64))
+}
@@ -0,0 +1,160 @@
+//! What the player sees under the fog. The field of view is cast from the player cell, walls
+//! stopping it, and the cells seen once are remembered.
+
+use serde::{Deserialize, Serialize};
+
+use crate::maze::{CellIndex, Maze};
+
+/// Turns the first octant, below the origin and left of the diagonal, into each of the eight
+/// octants: (xx, xy, yx, yy)
+const OCTANTS: [(i64, i64, i64, i64); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+/// Cells in sight and cells already explored, in row-major order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Vision {
+    visible: Vec<bool>,
+    explored: Vec<bool>,
+}
+
+impl Vision {
+    pub fn new(maze: &Maze) -> Vision {
+        let (w, h) = maze.dim();
+        Vision {
+            visible: vec![false; w * h],
+            explored: vec![false; w * h],
+        }
+    }
+
+    /// Looks around from each of `origins`, as far as `radius` cells
+    pub fn update<I: IntoIterator<Item = CellIndex>>(
+        &mut self,
+        maze: &Maze,
+        origins: I,
+        radius: u8,
+    ) {
+        // games saved before there was a field of view start with nothing explored
+        let (w, h) = maze.dim();
+        if self.visible.len() != w * h || self.explored.len() != w * h {
+            *self = Vision::new(maze);
+        }
+
+        self.visible.iter_mut().for_each(|visible| *visible = false);
+        for origin in origins {
+            field_of_view(maze, origin, radius as i64, |pos| {
+                let index = maze.index(pos);
+                self.visible[index] = true;
+                self.explored[index] = true;
+            });
+        }
+    }
+
+    pub fn is_visible(&self, maze: &Maze, pos: CellIndex) -> bool {
+        self.visible.get(maze.index(pos)).copied().unwrap_or(false)
+    }
+
+    /// Whether the cell was seen at some point, it may be out of sight now
+    pub fn is_explored(&self, maze: &Maze, pos: CellIndex) -> bool {
+        self.explored.get(maze.index(pos)).copied().unwrap_or(false)
+    }
+}
+
+/// Calls `mark` on every cell in sight from `origin`, no further than `radius` cells, using
+/// recursive shadow casting. Walls are seen but hide what is behind them.
+pub fn field_of_view<F: FnMut(CellIndex)>(maze: &Maze, origin: CellIndex, radius: i64, mark: F) {
+    let mut caster = Caster {
+        maze,
+        origin,
+        radius,
+        octant: OCTANTS[0],
+        mark,
+    };
+
+    (caster.mark)(origin);
+    for octant in &OCTANTS {
+        caster.octant = *octant;
+        caster.cast(1, 1., 0.);
+    }
+}
+
+/// Lights one octant at a time
+struct Caster<'m, F> {
+    maze: &'m Maze,
+    origin: CellIndex,
+    radius: i64,
+    octant: (i64, i64, i64, i64),
+    mark: F,
+}
+
+impl<F: FnMut(CellIndex)> Caster<'_, F> {
+    /// Lights the rows of the octant from `row` on, between the slopes `start` and `end`
+    fn cast(&mut self, row: i64, mut start: f32, end: f32) {
+        if start < end {
+            return;
+        }
+        let (xx, xy, yx, yy) = self.octant;
+        let mut next_start = start;
+
+        for distance in row..=self.radius {
+            let dy = -distance;
+            let mut blocked = false;
+
+            for dx in -distance..=0 {
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+
+                let pos = self.cell(dx * xx + dy * xy, dx * yx + dy * yy);
+                if let Some(pos) = pos {
+                    if dx * dx + dy * dy <= self.radius * self.radius {
+                        (self.mark)(pos);
+                    }
+                }
+                // past the edges is as good as a wall
+                let opaque = pos.is_none_or(|pos| self.maze.get(pos).is_wall());
+
+                if blocked {
+                    if opaque {
+                        next_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = next_start;
+                    }
+                } else if opaque && distance < self.radius {
+                    blocked = true;
+                    self.cast(distance + 1, start, left_slope);
+                    next_start = right_slope;
+                }
+            }
+
+            if blocked {
+                break;
+            }
+        }
+    }
+
+    /// The cell at (`dx`, `dy`) from the origin, if inside the maze
+    fn cell(&self, dx: i64, dy: i64) -> Option<CellIndex> {
+        let (w, h) = self.maze.dim();
+        let (x, y) = (self.origin.x as i64 + dx, self.origin.y as i64 + dy);
+
+        if (0..w as i64).contains(&x) && (0..h as i64).contains(&y) {
+            Some(CellIndex::from([x as usize, y as usize]))
+        } else {
+            None
+        }
+    }
+}